@@ -1,6 +1,6 @@
 #![no_std] // No usamos la biblioteca estándar de Rust
 
-use soroban_sdk::{contract, contractimpl, Env, String, Map, symbol_short};
+use soroban_sdk::{contract, contractclient, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, token, vec, xdr::ToXdr, Address, Bytes, BytesN, Env, Map, String, Vec};
 
 
 
@@ -10,10 +10,538 @@ const HISTORIAL_KEY: soroban_sdk::Symbol = symbol_short!("HISTORIAL");
 const RESPALDOS_KEY: soroban_sdk::Symbol = symbol_short!("RESPALDOS");
 const USUARIOS_KEY: soroban_sdk::Symbol = symbol_short!("USUARIOS");
 const NOTIFICAS_KEY: soroban_sdk::Symbol = symbol_short!("NOTIFICA");
+const SUSCRIPC_KEY: soroban_sdk::Symbol = symbol_short!("SUSCRIPC");
+const SUSCRIPT_KEY: soroban_sdk::Symbol = symbol_short!("SUSCRIPT");
 const SESIONES_KEY: soroban_sdk::Symbol = symbol_short!("SESIONES");
 const INFORMES_KEY: soroban_sdk::Symbol = symbol_short!("INFORMES");
 const EXPORTAC_KEY: soroban_sdk::Symbol = symbol_short!("EXPORTAC");
 const INTEGRA_KEY: soroban_sdk::Symbol = symbol_short!("INTEGRA");
+const ESTADIST_KEY: soroban_sdk::Symbol = symbol_short!("ESTADIST");
+const EXTERNOS_KEY: soroban_sdk::Symbol = symbol_short!("EXTERNOS");
+const DOCEXTER_KEY: soroban_sdk::Symbol = symbol_short!("DOCEXTER");
+const INSIGNIA_KEY: soroban_sdk::Symbol = symbol_short!("INSIGNIA");
+const EMISION_KEY: soroban_sdk::Symbol = symbol_short!("EMISION");
+const EMISORES_KEY: soroban_sdk::Symbol = symbol_short!("EMISORES");
+const VERIFICA_KEY: soroban_sdk::Symbol = symbol_short!("VERIFICA");
+const PRIVADOS_KEY: soroban_sdk::Symbol = symbol_short!("PRIVADOS");
+const ACCESOS_KEY: soroban_sdk::Symbol = symbol_short!("ACCESOS");
+const COMPROMI_KEY: soroban_sdk::Symbol = symbol_short!("COMPROMI");
+const INSTITUC_KEY: soroban_sdk::Symbol = symbol_short!("INSTITUC");
+const VERSIONES_KEY: soroban_sdk::Symbol = symbol_short!("VERSIONES");
+const ADMIN_KEY: soroban_sdk::Symbol = symbol_short!("ADMIN");
+const TIPOS_KEY: soroban_sdk::Symbol = symbol_short!("TIPOS");
+const DOCTIPO_KEY: soroban_sdk::Symbol = symbol_short!("DOCTIPO");
+const ATRIBUTOS_KEY: soroban_sdk::Symbol = symbol_short!("ATRIBUTOS");
+const CAMBIOATR_KEY: soroban_sdk::Symbol = symbol_short!("CAMBIOATR");
+const REGISTRO_KEY: soroban_sdk::Symbol = symbol_short!("REGISTRO");
+const BITACORA_KEY: soroban_sdk::Symbol = symbol_short!("BITACORA");
+const TARIFAS_KEY: soroban_sdk::Symbol = symbol_short!("TARIFAS");
+const CREDITOS_KEY: soroban_sdk::Symbol = symbol_short!("CREDITOS");
+const TITULARES_KEY: soroban_sdk::Symbol = symbol_short!("TITULARES");
+const POSESION_KEY: soroban_sdk::Symbol = symbol_short!("POSESION");
+const PARES_KEY: soroban_sdk::Symbol = symbol_short!("PARES");
+
+/// Estado que marca un documento como revocado.
+const ESTADO_REVOCADO: &str = "Revocado";
+/// Estados en los que un documento está vigente: supera la verificación y, si
+/// tiene titular, éste conserva su token. Cualquier otro (`Revocado`,
+/// `Anulado`, `Pendiente`...) lo invalida.
+const ESTADOS_VIGENTES: [&str; 2] = ["Activo", "Validado"];
+/// Rol con el que se da de alta el perfil del administrador.
+const ROL_ADMINISTRADOR: &str = "Administrador";
+/// Rol que hace falta tener vigente para emitir documentos.
+const ROL_EMISOR: &str = "Emisor";
+const NOMBRE_TOKEN: &str = "Certificado CertiWeb";
+const SIMBOLO_TOKEN: &str = "CERTI";
+
+/// Longitud máxima, en bytes, del valor que se presenta a `verificar_campo`.
+const MAX_VALOR_CAMPO: usize = 256;
+/// Atributos que puede tener como máximo un documento.
+const MAX_ATRIBUTOS: u32 = 16;
+/// Ledgers de vida que se renuevan a lo que toca una operación que paga
+/// tarifa (unos 180 días a 5 s por ledger), y umbral por debajo del cual se
+/// renuevan (unos 30 días).
+const TTL_EXTENSION: u32 = 3_110_400;
+const TTL_UMBRAL: u32 = 518_400;
+/// Verificaciones que se conservan por documento; las más antiguas se
+/// descartan (el total sigue contando en las estadísticas).
+const MAX_VERIFICACIONES: u32 = 32;
+/// Suscripciones que puede tener como máximo un documento (y, aparte, las que
+/// siguen a todos los documentos). Acota el trabajo de cada cambio de estado.
+const MAX_SUSCRIPCIONES_DOCUMENTO: u32 = 8;
+/// Suscripciones que puede tener a la vez una misma dirección.
+const MAX_SUSCRIPCIONES_SUSCRIPTOR: u32 = 16;
+/// Esquemas de `canal` que entiende el relé de notificaciones.
+const CANALES: [&str; 3] = ["webhook:", "smtp:", "archivo:"];
+
+// Límites de las entradas de texto, en bytes.
+const MAX_TITULO: u32 = 200;
+const MAX_ESTADO: u32 = 32;
+const MAX_NOMBRE: u32 = 100;
+const MAX_ROL: u32 = 32;
+const MAX_UBICACION: u32 = 256;
+const MAX_SISTEMA: u32 = 64;
+const MAX_ID_EXTERNO: u32 = 64;
+const MAX_MOTIVO: u32 = 256;
+const MAX_RESULTADO: u32 = 256;
+const MAX_LOGRO: u32 = 100;
+const MAX_CRITERIOS: u32 = 1024;
+const MAX_CANAL: u32 = 256;
+const MAX_FORMATO: u32 = 16;
+/// Margen, en segundos, que se admite para fechas posteriores al ledger
+/// (relojes de los sistemas que llaman ligeramente adelantados).
+const TOLERANCIA_FECHA: u64 = 300;
+/// Motivo de las enmiendas que aplica `sincronizar_documento`.
+const MOTIVO_SINCRONIZACION: &str = "Sincronización con el sistema académico";
+
+/// Errores de validación de las entradas. Cada campo de texto tiene su error
+/// de "vacío" y de "demasiado largo". Los códigos son estables: los errores
+/// nuevos se añaden al final, sin renumerar los existentes.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ErrorValidacion {
+    TituloVacio = 1,
+    TituloDemasiadoLargo = 2,
+    EstadoVacio = 3,
+    EstadoDemasiadoLargo = 4,
+    /// La fecha es 0.
+    FechaNula = 5,
+    /// La fecha es posterior al ledger más `TOLERANCIA_FECHA`.
+    FechaFutura = 6,
+    NombreVacio = 7,
+    NombreDemasiadoLargo = 8,
+    RolVacio = 9,
+    RolDemasiadoLargo = 10,
+    UbicacionVacia = 11,
+    UbicacionDemasiadoLarga = 12,
+    AutorVacio = 13,
+    AutorDemasiadoLargo = 14,
+    SistemaVacio = 15,
+    SistemaDemasiadoLargo = 16,
+    CanalVacio = 17,
+    CanalDemasiadoLargo = 18,
+    IdExternoVacio = 19,
+    IdExternoDemasiadoLargo = 20,
+    MotivoVacio = 21,
+    MotivoDemasiadoLargo = 22,
+    ResultadoVacio = 23,
+    ResultadoDemasiadoLargo = 24,
+    LogroVacio = 25,
+    LogroDemasiadoLargo = 26,
+    CriteriosVacios = 27,
+    CriteriosDemasiadoLargos = 28,
+    /// El vencimiento (de una emisión, un rol o un acceso) no es posterior
+    /// al ledger.
+    VencimientoPasado = 29,
+    /// El valor de un atributo o de un campo tipado supera `MAX_VALOR_CAMPO`
+    /// bytes.
+    ValorDemasiadoLargo = 30,
+    /// El canal no empieza por `webhook:`, `smtp:` ni `archivo:`, o no indica
+    /// destino.
+    CanalNoValido = 31,
+    FormatoVacio = 32,
+    FormatoDemasiadoLargo = 33,
+    /// Un campo de un documento tipado no tiene valor.
+    ValorVacio = 34,
+    /// El documento tipado tiene más de `MAX_ATRIBUTOS` campos.
+    DemasiadosCampos = 35,
+}
+
+/// Instantánea del registro de documentos tomada por `realizar_respaldo`.
+///
+/// `digest` es un hash encadenado sobre todos los documentos ordenados por ID:
+/// `h_0 = [0; 32]`, `h_i = sha256(h_{i-1} || sha256(xdr(id, titulo, estado, fecha)))`.
+/// Cualquier exportación fuera de la cadena puede recalcularlo y compararlo
+/// con el que devuelve `consultar_respaldo`. `fecha` es el momento del ledger
+/// en que se tomó.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Respaldo {
+    pub fecha: u64,
+    pub ubicacion: String,
+    pub autor: String,
+    pub ledger: u32,
+    pub total_documentos: u32,
+    pub digest: BytesN<32>,
+}
+
+/// Exportación del registro registrada por `exportar_datos`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Exportacion {
+    pub formato: String,
+    pub hash: BytesN<32>,
+    pub filas: u32,
+    pub fecha: u64,
+}
+
+/// Contadores agregados del registro, mantenidos de forma incremental por
+/// `registrar_documento`, `actualizar_estado`, `registrar_emision` y
+/// `registrar_verificacion`. Los meses se expresan como `AAAAMM`
+/// (p. ej. `202201`); `por_institucion` cuenta los documentos emitidos con la
+/// firma de cada institución.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Estadisticas {
+    pub total_documentos: u32,
+    pub por_estado: Map<String, u32>,
+    pub por_mes: Map<u32, u32>,
+    pub verificaciones_por_mes: Map<u32, u32>,
+    pub por_institucion: Map<i32, u32>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TipoInforme {
+    General,
+    PorEstado,
+    PorMes,
+    Verificaciones,
+    PorInstitucion,
+}
+
+/// Rango de fechas (timestamps Unix, ambos inclusive) que cubre un informe.
+/// Los contadores se llevan por mes, así que el rango se amplía a los meses
+/// completos que contienen `desde` y `hasta`: un rango de un día cuenta todo
+/// su mes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rango {
+    pub desde: u64,
+    pub hasta: u64,
+}
+
+/// Informe calculado por `generar_informe`. Las secciones que no corresponden
+/// al `tipo` pedido quedan vacías.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Informe {
+    pub tipo: TipoInforme,
+    pub rango: Rango,
+    pub fecha_generacion: u64,
+    pub total_documentos: u32,
+    pub por_estado: Map<String, u32>,
+    pub por_mes: Map<u32, u32>,
+    pub verificaciones: u32,
+    pub por_institucion: Map<i32, u32>,
+}
+
+/// Eventos del registro a los que se puede suscribir una dirección.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TipoEvento {
+    /// Cualquier cambio de estado del documento (incluida la revocación).
+    CambioEstado,
+    /// El documento pasó al estado `Revocado`.
+    Revocacion,
+    /// Se registró una verificación del documento.
+    Verificacion,
+}
+
+/// Suscripción creada con `configurar_notificacion`.
+/// `id_documento = None` suscribe a todos los documentos del registro.
+/// `canal` indica al relé fuera de la cadena cómo entregar el aviso, p. ej.
+/// `webhook:https://...`, `smtp:titular@example.com` o `archivo:/ruta`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Suscripcion {
+    pub suscriptor: Address,
+    pub id_documento: Option<i32>,
+    pub evento: TipoEvento,
+    pub canal: String,
+}
+
+/// Datos del evento `("notif", suscriptor, id_documento)` que consume el relé.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Notificacion {
+    pub id_notificacion: i32,
+    pub evento: TipoEvento,
+    pub canal: String,
+    pub estado: String,
+}
+
+/// Sistema académico externo (SIS) autorizado a sincronizar documentos de una
+/// institución registrada. `ultimo_cursor` es la marca de avance que envía el
+/// propio SIS (p. ej. su número de cambio) y permite reanudar la
+/// sincronización; nunca retrocede.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Integracion {
+    pub nombre_sistema: String,
+    pub operador: Address,
+    pub id_institucion: i32,
+    pub ultimo_cursor: u64,
+    pub ultima_sincronizacion: u64,
+    pub total_sincronizados: u32,
+}
+
+/// Vínculo de un documento con su registro en el sistema académico.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdExterno {
+    pub id_integracion: i32,
+    pub id_externo: String,
+}
+
+/// Datos de una insignia (micro-credencial) registrada con `registrar_insignia`.
+/// `hash_imagen` es el SHA-256 de la imagen de la insignia, que se publica
+/// fuera de la cadena.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Insignia {
+    pub logro: String,
+    pub criterios: String,
+    pub hash_imagen: BytesN<32>,
+}
+
+/// Datos de emisión de un documento: quién lo emitió, a quién y hasta cuándo
+/// es válido. `hash_documento` es el SHA-256 del archivo emitido (p. ej. el
+/// PDF) y `hash_titular` el de un identificador del titular (p. ej. su DID o
+/// matrícula), para no publicar datos personales. `fecha` es el momento del
+/// ledger en que se registró la emisión, con el que se comprueba que el
+/// emisor era una clave vigente de `id_institucion`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Emision {
+    pub emisor: Address,
+    pub id_institucion: Option<i32>,
+    pub fecha: u64,
+    pub hash_documento: BytesN<32>,
+    pub hash_titular: BytesN<32>,
+    pub vencimiento: Option<u64>,
+}
+
+/// Motivo por el que un documento no supera la verificación.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MotivoRechazo {
+    NoEncontrado,
+    EmisorInactivo,
+    /// El emisor no era una clave vigente de su institución al emitir.
+    EmisorNoAutorizado,
+    Revocado,
+    /// El estado del documento no es uno de `ESTADOS_VIGENTES`.
+    NoVigente,
+    Vencido,
+    /// El hash presentado no es el del documento emitido.
+    HashNoCoincide,
+    /// Su tipo exige firma y el documento no tiene datos de emisión.
+    SinFirma,
+    /// El registro par no da el documento por válido (`verificar_externo`
+    /// no conoce sus motivos).
+    RechazadoPorRegistro,
+    /// No se pudo consultar al registro par (`verificar_externo`).
+    RegistroNoDisponible,
+}
+
+/// Resultado de `verificar_documento_publico`: todo lo que un tercero
+/// necesita para aceptar un documento en una sola consulta (estado, emisor,
+/// hash del titular y vencimiento). `valido` es `true` sólo si `motivos` está
+/// vacío. Los datos de emisión son `None` si el documento no los tiene
+/// registrados; `hash_titular` son los 32 bytes de `Emision::hash_titular`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResultadoVerificacion {
+    pub id_documento: i32,
+    pub valido: bool,
+    pub motivos: Vec<MotivoRechazo>,
+    pub estado: Option<String>,
+    pub emisor: Option<Address>,
+    pub hash_titular: Option<Bytes>,
+    pub vencimiento: Option<u64>,
+}
+
+/// Entrada del registro de verificaciones de un documento.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Verificacion {
+    pub verificador: Address,
+    pub fecha: u64,
+    pub valido: bool,
+}
+
+/// Campos privados de un documento (p. ej. calificaciones) y su titular.
+/// El almacenamiento del contrato es legible por cualquiera, así que los
+/// valores deben guardarse cifrados o como hash: el contrato sólo controla
+/// quién puede pedirlos con `obtener_campos_privados`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CamposPrivados {
+    pub titular: Address,
+    pub campos: Map<soroban_sdk::Symbol, Bytes>,
+}
+
+/// Dirección de firma de una institución y el periodo en que estuvo vigente.
+/// `hasta` es `None` para la clave actual.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaveFirma {
+    pub direccion: Address,
+    pub desde: u64,
+    pub hasta: Option<u64>,
+}
+
+/// Institución emisora con el historial de sus direcciones de firma, de la
+/// más antigua a la actual.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Institucion {
+    pub nombre: String,
+    pub claves: Vec<ClaveFirma>,
+}
+
+/// Qué firma exige un tipo de documento para considerarse válido.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PoliticaFirma {
+    /// No exige datos de emisión.
+    Ninguna,
+    /// Exige `registrar_emision` por cualquier emisor.
+    Emisor,
+    /// Exige `registrar_emision` con una clave vigente de una institución.
+    Institucional,
+}
+
+/// Tipo de documento (título, certificado de estudios, constancia...) dado
+/// de alta por el administrador. `vigencia` son los segundos de validez que
+/// se aplican al emitir si no se indica un vencimiento.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TipoDocumento {
+    pub nombre: String,
+    pub campos_requeridos: Vec<soroban_sdk::Symbol>,
+    pub vigencia: Option<u64>,
+    pub politica_firma: PoliticaFirma,
+}
+
+/// Tipo y campos de un documento registrado con `registrar_documento_tipado`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DocumentoTipado {
+    pub id_tipo: i32,
+    pub campos: Map<soroban_sdk::Symbol, String>,
+}
+
+/// Datos que corrige `enmendar_documento`; `None` deja el valor actual.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cambios {
+    pub titulo: Option<String>,
+    pub fecha_emision_academica: Option<u64>,
+}
+
+/// Una versión de un documento. La versión 1 es el registro original; cada
+/// enmienda añade otra que apunta a la `anterior` y marca esa como
+/// `reemplazada`. `registrada` es el momento del ledger en que se creó la
+/// enmienda (0 en la versión original).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Version {
+    pub numero: u32,
+    pub anterior: Option<u32>,
+    pub titulo: String,
+    pub fecha_emision_academica: u64,
+    pub motivo: String,
+    pub registrada: u64,
+    pub reemplazada: bool,
+}
+
+/// Un cambio en los atributos de un documento. `anterior` es `None` si el
+/// atributo no existía y `valor` es `None` si se eliminó.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CambioAtributo {
+    pub clave: soroban_sdk::Symbol,
+    pub anterior: Option<String>,
+    pub valor: Option<String>,
+    pub fecha: u64,
+}
+
+/// Un rol concedido a un usuario. `hasta` es el momento del ledger en que
+/// deja de valer (exclusivo); `None` si no vence.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConcesionRol {
+    pub rol: String,
+    pub hasta: Option<u64>,
+}
+
+/// Perfil de un usuario del sistema, guardado por su dirección Stellar.
+/// `hash_email` es el SHA-256 del correo (32 bytes), para no publicarlo.
+/// Un usuario desactivado conserva su registro pero no supera ninguna
+/// comprobación de permisos ni de firma.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Usuario {
+    pub nombre: Option<String>,
+    pub hash_email: Option<Bytes>,
+    pub roles: Vec<ConcesionRol>,
+    pub activo: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccionUsuario {
+    Creado,
+    RolAsignado,
+    RolRevocado,
+    Desactivado,
+    Reactivado,
+    Eliminado,
+}
+
+/// Entrada de la bitácora de un usuario. `fecha` es la del ledger.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CambioUsuario {
+    pub accion: AccionUsuario,
+    pub fecha: u64,
+}
+
+/// Tarifas por operación, en unidades mínimas de `token` (cualquier contrato
+/// de activo Stellar). Lo cobrado queda en el contrato hasta que el
+/// administrador lo retira a `tesoreria` con `retirar_fondos`. Una tarifa 0
+/// no cobra nada ni renueva el almacenamiento.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Tarifas {
+    pub token: Address,
+    pub tesoreria: Address,
+    /// La paga el emisor en `registrar_emision`.
+    pub emision: i128,
+    /// La paga el verificador en `registrar_verificacion` si no tiene
+    /// créditos; también es el precio de cada crédito prepagado.
+    pub verificacion: i128,
+}
+
+/// Metadatos del token intransferible de un documento.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetadatosCertificado {
+    pub id_documento: i32,
+    pub titular: Address,
+    pub titulo: String,
+    pub fecha_emision_academica: u64,
+}
+
+/// Interfaz mínima y estable para que otros contratos comprueben credenciales
+/// (p. ej. un contrato de becas que exige un título válido). Se llama con
+/// `VerificadorClient::new(&env, &registro)`; sus firmas no cambiarán aunque
+/// cambie el resto del contrato.
+#[contractclient(name = "VerificadorClient")]
+pub trait Verificador {
+    /// `true` si el documento tiene datos de emisión y supera todas las
+    /// comprobaciones de `verificar_documento_publico`. Como
+    /// `registrar_emision` exige el rol `Emisor` vigente (o una clave
+    /// institucional) al firmar, que ese rol venza después no lo invalida.
+    fn es_valido(env: Env, id_documento: i32) -> bool;
+
+    /// `true` si `titular` tiene el token de algún documento válido del tipo
+    /// `id_tipo` (ver `registrar_tipo` y `vincular_titular`).
+    fn titular_tiene_credencial(env: Env, titular: Address, id_tipo: i32) -> bool;
+}
 
 #[contract]
 pub struct DocumentosContract;
@@ -21,33 +549,43 @@ pub struct DocumentosContract;
 #[contractimpl]
 impl DocumentosContract {
     // --- CREATE (Dar de alta un documento) ---
-    /// Añade un nuevo documento al registro.
-    /// Panics si el documento con el mismo ID ya existe.
+    /// Añade un nuevo documento al registro. Requiere la firma de
+    /// `registrador`, que debe tener un perfil de usuario activo.
+    /// Panics si el documento con el mismo ID ya existe o si el título, el
+    /// estado o la fecha no son válidos (`ErrorValidacion`).
     ///
     /// # Arguments
     ///
     /// * `id_documento` - El ID del documento (i32).
+    /// * `registrador` - Quien da de alta el documento (Address).
     /// * `titulo` - El título del documento (String).
     /// * `estado` - El estado del documento (String).
-    /// * `fecha` - La fecha de creación del documento (u64).
-    pub fn registrar_documento(env: Env, id_documento: i32, titulo: String, estado: String, fecha: u64) {
-        // Obtenemos el mapa de documentos del almacenamiento persistente.
-        // Si no existe, crea un mapa vacío.
-        let mut documentos: Map<i32, (String, String, u64)> = env
+    /// * `fecha_emision_academica` - La fecha de emisión académica que figura
+    ///   en el documento (u64). Es la fecha que devuelve `obtener_documento`;
+    ///   el momento del registro se toma del ledger (`obtener_fecha_registro`).
+    pub fn registrar_documento(
+        env: Env,
+        id_documento: i32,
+        registrador: Address,
+        titulo: String,
+        estado: String,
+        fecha_emision_academica: u64,
+    ) {
+        Self::validar_documento(&env, &titulo, &estado, fecha_emision_academica);
+        Self::autorizar(&env, &registrador);
+        Self::alta_documento(&env, id_documento, titulo, estado, fecha_emision_academica);
+    }
+
+    /// Momento del ledger en que se registró un documento, o `None` si no
+    /// existe.
+    pub fn obtener_fecha_registro(env: Env, id_documento: i32) -> Option<u64> {
+        let registros: Map<i32, u64> = env
             .storage()
             .persistent()
-            .get(&DOCUMENTS_KEY)
-            .unwrap_or(Map::new(&env)); // Crea un mapa vacío si no hay nada
-
-        // Verificamos si el documento ya existe para evitar duplicados
-        if documentos.contains_key(id_documento) {
-            panic!("Documento con el mismo ID ya existe"); // Error si el documento ya está registrado
-        }
+            .get(&REGISTRO_KEY)
+            .unwrap_or(Map::new(&env));
 
-        // Añadimos el nuevo documento con sus detalles (titulo, estado y fecha)
-        documentos.set(id_documento, (titulo, estado, fecha));
-        // Guardamos el mapa actualizado en el almacenamiento persistente
-        env.storage().persistent().set(&DOCUMENTS_KEY, &documentos);
+        registros.get(id_documento)
     }
  //metodo para obtener todos los documentos   
     pub fn obtener_documento(env: Env, id_documento: i32) -> Option<(String, String, u64)> {
@@ -59,8 +597,161 @@ impl DocumentosContract {
     
         documentos.get(id_documento)
     }
+    // --- Insignias (Open Badges) ---
+    /// Registra una insignia: un documento como los de `registrar_documento`
+    /// más el logro, sus criterios y el hash de la imagen.
+    /// Panics si el documento con el mismo ID ya existe.
+    ///
+    /// # Arguments
+    ///
+    /// * `id_documento`, `registrador`, `titulo`, `estado`,
+    ///   `fecha_emision_academica` - Igual que en `registrar_documento`.
+    /// * `insignia` - El logro, sus criterios y el hash de la imagen (Insignia).
+    pub fn registrar_insignia(
+        env: Env,
+        id_documento: i32,
+        registrador: Address,
+        titulo: String,
+        estado: String,
+        fecha_emision_academica: u64,
+        insignia: Insignia,
+    ) {
+        Self::validar_texto(&env, &insignia.logro, MAX_LOGRO, ErrorValidacion::LogroVacio, ErrorValidacion::LogroDemasiadoLargo);
+        Self::validar_texto(&env, &insignia.criterios, MAX_CRITERIOS, ErrorValidacion::CriteriosVacios, ErrorValidacion::CriteriosDemasiadoLargos);
+        Self::registrar_documento(env.clone(), id_documento, registrador, titulo, estado, fecha_emision_academica);
+
+        let mut insignias: Map<i32, Insignia> = env
+            .storage()
+            .persistent()
+            .get(&INSIGNIA_KEY)
+            .unwrap_or(Map::new(&env));
+
+        insignias.set(id_documento, insignia);
+        env.storage().persistent().set(&INSIGNIA_KEY, &insignias);
+    }
+
+    /// Devuelve los datos de insignia de un documento, o `None` si el
+    /// documento no es una insignia.
+    pub fn obtener_insignia(env: Env, id_documento: i32) -> Option<Insignia> {
+        let insignias: Map<i32, Insignia> = env
+            .storage()
+            .persistent()
+            .get(&INSIGNIA_KEY)
+            .unwrap_or(Map::new(&env));
+
+        insignias.get(id_documento)
+    }
+
+    // --- Administración ---
+    /// Fija el administrador del registro al desplegar el contrato, en la
+    /// misma transacción, para que nadie más pueda reclamarlo, y le da de
+    /// alta un perfil con el rol `ROL_ADMINISTRADOR`.
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+        let usuario = Usuario {
+            nombre: None,
+            hash_email: None,
+            roles: vec![&env, ConcesionRol { rol: String::from_str(&env, ROL_ADMINISTRADOR), hasta: None }],
+            activo: true,
+        };
+        Self::guardar_usuario(&env, &admin, usuario);
+        Self::anotar_usuario(&env, &admin, AccionUsuario::Creado);
+    }
+
+    pub fn obtener_admin(env: Env) -> Address {
+        Self::admin(&env)
+    }
+
+    // --- Tipos de documento ---
+    /// Da de alta un tipo de documento. Un tipo no se modifica una vez
+    /// registrado: su política de firma decide si los documentos ya emitidos
+    /// verifican. Requiere la firma del administrador.
+    /// Panics si ya existe un tipo con ese ID.
+    pub fn registrar_tipo(env: Env, id_tipo: i32, tipo: TipoDocumento) {
+        Self::validar_texto(&env, &tipo.nombre, MAX_NOMBRE, ErrorValidacion::NombreVacio, ErrorValidacion::NombreDemasiadoLargo);
+        Self::admin(&env).require_auth();
+
+        let mut tipos: Map<i32, TipoDocumento> = env
+            .storage()
+            .persistent()
+            .get(&TIPOS_KEY)
+            .unwrap_or(Map::new(&env));
+
+        if tipos.contains_key(id_tipo) {
+            panic!("Tipo con ese ID ya existe");
+        }
+        tipos.set(id_tipo, tipo);
+        env.storage().persistent().set(&TIPOS_KEY, &tipos);
+    }
+
+    pub fn obtener_tipo(env: Env, id_tipo: i32) -> Option<TipoDocumento> {
+        let tipos: Map<i32, TipoDocumento> = env
+            .storage()
+            .persistent()
+            .get(&TIPOS_KEY)
+            .unwrap_or(Map::new(&env));
+
+        tipos.get(id_tipo)
+    }
+
+    /// Registra un documento de un tipo dado, como `registrar_documento`, con
+    /// sus campos (a lo sumo `MAX_ATRIBUTOS`, cada uno con un valor de 1 a
+    /// `MAX_VALOR_CAMPO` bytes). Panics si el tipo no existe o falta alguno de
+    /// sus campos requeridos.
+    #[allow(clippy::too_many_arguments)]
+    pub fn registrar_documento_tipado(
+        env: Env,
+        id_documento: i32,
+        registrador: Address,
+        id_tipo: i32,
+        titulo: String,
+        estado: String,
+        fecha_emision_academica: u64,
+        campos: Map<soroban_sdk::Symbol, String>,
+    ) {
+        if campos.len() > MAX_ATRIBUTOS {
+            panic_with_error!(&env, ErrorValidacion::DemasiadosCampos);
+        }
+        for valor in campos.values().iter() {
+            Self::validar_texto(&env, &valor, MAX_VALOR_CAMPO as u32, ErrorValidacion::ValorVacio, ErrorValidacion::ValorDemasiadoLargo);
+        }
+        let tipo = match Self::obtener_tipo(env.clone(), id_tipo) {
+            Some(tipo) => tipo,
+            None => panic!("Tipo de documento no encontrado"),
+        };
+        for campo in tipo.campos_requeridos.iter() {
+            if !campos.contains_key(campo) {
+                panic!("Falta un campo requerido por el tipo de documento");
+            }
+        }
+
+        Self::registrar_documento(env.clone(), id_documento, registrador, titulo, estado, fecha_emision_academica);
+
+        let mut tipados: Map<i32, DocumentoTipado> = env
+            .storage()
+            .persistent()
+            .get(&DOCTIPO_KEY)
+            .unwrap_or(Map::new(&env));
+
+        tipados.set(id_documento, DocumentoTipado { id_tipo, campos });
+        env.storage().persistent().set(&DOCTIPO_KEY, &tipados);
+    }
+
+    /// Devuelve el tipo y los campos de un documento, o `None` si se registró
+    /// sin tipo.
+    pub fn obtener_documento_tipado(env: Env, id_documento: i32) -> Option<DocumentoTipado> {
+        let tipados: Map<i32, DocumentoTipado> = env
+            .storage()
+            .persistent()
+            .get(&DOCTIPO_KEY)
+            .unwrap_or(Map::new(&env));
+
+        tipados.get(id_documento)
+    }
 //metodo para realizar una consulta al historial   /// Añade una entrada al historial de un documento.
-    pub fn consulta_historial(env: Env, id_historial: i32, fecha: u64, resultado: String) {
+    /// La fecha de la entrada es la del ledger.
+    pub fn consulta_historial(env: Env, id_historial: i32, resultado: String) {
+        Self::validar_texto(&env, &resultado, MAX_RESULTADO, ErrorValidacion::ResultadoVacio, ErrorValidacion::ResultadoDemasiadoLargo);
         let mut historial: Map<i32, (u64, String)> = env
             .storage()
             .persistent()
@@ -71,7 +762,7 @@ impl DocumentosContract {
             panic!("Historial con el mismo ID ya existe");
         }
 
-        historial.set(id_historial, (fecha, resultado));
+        historial.set(id_historial, (env.ledger().timestamp(), resultado));
         env.storage().persistent().set(&HISTORIAL_KEY, &historial);
     }
     //  --- Obtener una entrada del historial por ID ---
@@ -84,16 +775,28 @@ impl DocumentosContract {
 
         historial.get(id_historial)
     }
-// --- Realizar un respaldo de un documento --- 
-    /// Añade un respaldo de un documento al registro.
+// --- Realizar un respaldo del registro --- 
+    /// Toma una instantánea del registro de documentos y la guarda junto con
+    /// los datos del respaldo (fecha del ledger, ubicación del archivo y autor).
+    /// Requiere la firma del administrador.
+    /// Panics si ya existe un respaldo con el mismo ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id_respaldo` - El ID del respaldo (i32).
+    /// * `ubicacion` - Dónde se guardó la exportación fuera de la cadena (String).
+    /// * `autor` - Quién realizó el respaldo (String).
     pub fn realizar_respaldo(
         env: Env,
         id_respaldo: i32,
-        fecha: u64,
         ubicacion: String,
         autor: String,
     ) {
-        let mut respaldos: Map<i32, (u64, String, String)> = env
+        Self::validar_texto(&env, &ubicacion, MAX_UBICACION, ErrorValidacion::UbicacionVacia, ErrorValidacion::UbicacionDemasiadoLarga);
+        Self::validar_texto(&env, &autor, MAX_NOMBRE, ErrorValidacion::AutorVacio, ErrorValidacion::AutorDemasiadoLargo);
+        Self::autorizar(&env, &Self::admin(&env));
+
+        let mut respaldos: Map<i32, Respaldo> = env
             .storage()
             .persistent()
             .get(&RESPALDOS_KEY)
@@ -101,15 +804,30 @@ impl DocumentosContract {
     
         if respaldos.contains_key(id_respaldo) {
             panic!("Respaldo con ese ID ya existe");
-        }  
-        respaldos.set(id_respaldo, (fecha, ubicacion, autor));
+        }
+
+        let documentos: Map<i32, (String, String, u64)> = env
+            .storage()
+            .persistent()
+            .get(&DOCUMENTS_KEY)
+            .unwrap_or(Map::new(&env));
+
+        let respaldo = Respaldo {
+            fecha: env.ledger().timestamp(),
+            ubicacion,
+            autor,
+            ledger: env.ledger().sequence(),
+            total_documentos: documentos.len(),
+            digest: Self::digest_registro(&env, documentos.iter()),
+        };
+        respaldos.set(id_respaldo, respaldo);
         env.storage().persistent().set(&RESPALDOS_KEY, &respaldos);
     }
  // --- Obtener un respaldo por ID ---
-    /// Devuelve un respaldo de un documento por su ID.
+    /// Devuelve un respaldo (con el digest de la instantánea) por su ID.
     /// Devuelve `None` si no existe.   
-    pub fn consultar_respaldo(env: Env, id_respaldo: i32) -> Option<(u64, String, String)> {
-        let respaldos: Map<i32, (u64, String, String)> = env
+    pub fn consultar_respaldo(env: Env, id_respaldo: i32) -> Option<Respaldo> {
+        let respaldos: Map<i32, Respaldo> = env
             .storage()
             .persistent()
             .get(&RESPALDOS_KEY)
@@ -118,10 +836,6 @@ impl DocumentosContract {
         respaldos.get(id_respaldo)
     }
 
-
-
-
-
 // ConsultaDocumento
 // pub fn buscar_documentos(env: Env, criterio: String) -> Vec<(i32, String, String, u64)> {
 //     let mut resultados: Vec<(i32, String, String, u64)> = Vec::new(&env);
@@ -179,180 +893,2042 @@ impl DocumentosContract {
 
 
 // ValidacionDocumento
-pub fn verificar_documento(env: Env, id_documento: i32, nuevo_resultado: String) {
-    let mut historial: Map<i32, (u64, String)> = env
-        .storage()
-        .persistent()
-        .get(&HISTORIAL_KEY)
-        .unwrap_or(Map::new(&env));
+/// Verifica un documento como `verificar_documento_publico` y deja constancia
+/// de quién lo verificó y cuándo; se conservan las `MAX_VERIFICACIONES` más
+/// recientes de cada documento. Requiere la firma del verificador.
+pub fn registrar_verificacion(
+    env: Env,
+    id_documento: i32,
+    verificador: Address,
+    hash_documento: Option<BytesN<32>>,
+) -> ResultadoVerificacion {
+    Self::autenticar(&env, &verificador);
+    let con_credito = Self::debitar_credito(&env, &verificador);
+    let pagada = con_credito || Self::cobrar(&env, &verificador, |tarifas| tarifas.verificacion);
+
+    let resultado = Self::verificar_documento_publico(env.clone(), id_documento, hash_documento);
+    // Si el contrato no tiene el documento, la consulta no se cobra
+    if con_credito && resultado.motivos.contains(MotivoRechazo::NoEncontrado) {
+        Self::abonar_creditos(&env, &verificador, 1);
+    }
 
     let fecha_actual = env.ledger().timestamp();
-    historial.set(id_documento, (fecha_actual, nuevo_resultado));
-    env.storage().persistent().set(&HISTORIAL_KEY, &historial);
+    let mut registro = Self::obtener_verificaciones(env.clone(), id_documento);
+    if registro.len() >= MAX_VERIFICACIONES {
+        registro.pop_front();
+    }
+    registro.push_back(Verificacion {
+        verificador,
+        fecha: fecha_actual,
+        valido: resultado.valido,
+    });
+    env.storage().persistent().set(&(VERIFICA_KEY, id_documento), &registro);
+    if pagada {
+        env.storage()
+            .persistent()
+            .extend_ttl(&(VERIFICA_KEY, id_documento), TTL_UMBRAL, TTL_EXTENSION);
+        Self::extender_ttl(&env, &[DOCUMENTS_KEY, EMISION_KEY, EMISORES_KEY, ESTADIST_KEY, CREDITOS_KEY]);
+    }
+
+    let mut estadisticas = Self::estadisticas(&env);
+    Self::sumar(&mut estadisticas.verificaciones_por_mes, Self::mes(fecha_actual), 1);
+    env.storage().persistent().set(&ESTADIST_KEY, &estadisticas);
+
+    if let Some(estado) = &resultado.estado {
+        Self::notificar(&env, id_documento, TipoEvento::Verificacion, estado);
+    }
+    resultado
+}
+
+/// Devuelve las verificaciones registradas de un documento (las
+/// `MAX_VERIFICACIONES` más recientes), de la más antigua a la más reciente.
+pub fn obtener_verificaciones(env: Env, id_documento: i32) -> Vec<Verificacion> {
+    env.storage()
+        .persistent()
+        .get(&(VERIFICA_KEY, id_documento))
+        .unwrap_or(Vec::new(&env))
 }
 
-pub fn actualizar_estado(env: Env, id_documento: i32, nuevo_estado: String) {
-    let mut documentos: Map<i32, (soroban_sdk::String, soroban_sdk::String, u64)> = env
+/// Marca a un emisor como inactivo (p. ej. si su clave se ha visto
+/// comprometida): sus documentos dejan de superar la verificación.
+/// Requiere la firma del emisor.
+pub fn desactivar_emisor(env: Env, emisor: Address) {
+    Self::autenticar(&env, &emisor);
+
+    let mut emisores: Map<Address, bool> = env
         .storage()
         .persistent()
-        .get(&DOCUMENTS_KEY)
+        .get(&EMISORES_KEY)
         .unwrap_or(Map::new(&env));
 
-    if let Some((titulo, _, fecha)) = documentos.get(id_documento) {
-        documentos.set(id_documento, (titulo.clone(), nuevo_estado, fecha));
-        env.storage().persistent().set(&DOCUMENTS_KEY, &documentos);
-    } else {
-        panic!("Documento no encontrado");
-    }
+    emisores.set(emisor, false);
+    env.storage().persistent().set(&EMISORES_KEY, &emisores);
 }
 
-// GestionUsuarios
-pub fn crear_usuario(env: Env, id_usuario: i32, nombre: String, rol: String) {
-    let mut usuarios: Map<i32, (String, String)> = env
+/// Un emisor está activo mientras no se haya desactivado.
+pub fn emisor_activo(env: Env, emisor: Address) -> bool {
+    let emisores: Map<Address, bool> = env
         .storage()
         .persistent()
-        .get(&USUARIOS_KEY)
+        .get(&EMISORES_KEY)
         .unwrap_or(Map::new(&env));
 
-    if usuarios.contains_key(id_usuario) {
-        panic!("Usuario ya existe");
+    emisores.get(emisor).unwrap_or(true)
+}
+
+// --- Tarifas ---
+/// Fija el token, la tesorería y las tarifas por operación. Lo que se paga
+/// con tarifa renueva la vida (TTL) del almacenamiento que toca. Requiere la
+/// firma del administrador.
+/// Panics si alguna tarifa es negativa, o si se cambia de token mientras
+/// quedan créditos prepagados o fondos sin retirar en el actual.
+pub fn configurar_tarifas(env: Env, tarifas: Tarifas) {
+    Self::admin(&env).require_auth();
+    if tarifas.emision < 0 || tarifas.verificacion < 0 {
+        panic!("Tarifa negativa");
     }
+    if let Some(actuales) = Self::obtener_tarifas(env.clone()) {
+        if actuales.token != tarifas.token {
+            let creditos: Map<Address, u32> = env
+                .storage()
+                .persistent()
+                .get(&CREDITOS_KEY)
+                .unwrap_or(Map::new(&env));
+            if creditos.values().iter().any(|saldo| saldo > 0) {
+                panic!("Quedan créditos pagados con el token actual");
+            }
+            if token::Client::new(&env, &actuales.token).balance(&env.current_contract_address()) > 0 {
+                panic!("Quedan fondos sin retirar en el token actual");
+            }
+        }
+    }
+    env.storage().persistent().set(&TARIFAS_KEY, &tarifas);
+}
 
-    usuarios.set(id_usuario, (nombre, rol));
-    env.storage().persistent().set(&USUARIOS_KEY, &usuarios);
+pub fn obtener_tarifas(env: Env) -> Option<Tarifas> {
+    env.storage().persistent().get(&TARIFAS_KEY)
 }
 
-pub fn asignar_rol(env: Env, id_usuario: i32, nuevo_rol: String) {
-    let mut usuarios: Map<i32, (String, String)> = env
-        .storage()
-        .persistent()
-        .get(&USUARIOS_KEY)
-        .unwrap_or(Map::new(&env));
+/// Transfiere `monto` de lo cobrado a la tesorería. Requiere la firma del
+/// administrador.
+/// Panics si no hay tarifas configuradas, el monto no es positivo o supera
+/// el saldo del contrato.
+pub fn retirar_fondos(env: Env, monto: i128) {
+    Self::admin(&env).require_auth();
+    let tarifas = match Self::obtener_tarifas(env.clone()) {
+        Some(tarifas) => tarifas,
+        None => panic!("Tarifas no configuradas"),
+    };
+    if monto <= 0 {
+        panic!("Monto inválido");
+    }
 
-    if let Some((nombre, _)) = usuarios.get(id_usuario) {
-        usuarios.set(id_usuario, (nombre, nuevo_rol));
-        env.storage().persistent().set(&USUARIOS_KEY, &usuarios);
-    } else {
-        panic!("Usuario no encontrado");
+    let token = token::Client::new(&env, &tarifas.token);
+    if token.balance(&env.current_contract_address()) < monto {
+        panic!("Fondos insuficientes");
     }
+    token.transfer(&env.current_contract_address(), &tarifas.tesoreria, &monto);
 }
 
-// ConfiguracionNotificaciones
-pub fn configurar_notificacion(env: Env, id_notificacion: i32, tipo: String) {
-    let mut notificaciones: Map<i32, String> = env
-        .storage()
-        .persistent()
-        .get(&NOTIFICAS_KEY)
-        .unwrap_or(Map::new(&env));
+// --- Créditos de verificación ---
+/// Compra `creditos` verificaciones prepagadas para `cuenta` (p. ej. un
+/// empleador) al precio de la tarifa de verificación. Cada
+/// `registrar_verificacion` de la cuenta consume un crédito en lugar de la
+/// tarifa; si el documento no existe, el crédito se devuelve, y si la llamada
+/// falla no se consume. Requiere la firma de `cuenta`.
+/// Panics si no hay tarifa de verificación configurada o `creditos` es 0.
+pub fn depositar_creditos(env: Env, cuenta: Address, creditos: u32) {
+    Self::autenticar(&env, &cuenta);
+    let tarifas = match Self::obtener_tarifas(env.clone()) {
+        Some(tarifas) if tarifas.verificacion > 0 => tarifas,
+        _ => panic!("Tarifa de verificación no configurada"),
+    };
+    if creditos == 0 {
+        panic!("Monto inválido");
+    }
 
-    notificaciones.set(id_notificacion, tipo);
-    env.storage().persistent().set(&NOTIFICAS_KEY, &notificaciones);
+    let monto = tarifas.verificacion * creditos as i128;
+    token::Client::new(&env, &tarifas.token).transfer(&cuenta, &env.current_contract_address(), &monto);
+    Self::abonar_creditos(&env, &cuenta, creditos);
 }
 
-pub fn enviar_notificacion(env: Env, id_notificacion: i32) -> Option<String> {
-    let notificaciones: Map<i32, String> = env
+pub fn saldo_creditos(env: Env, cuenta: Address) -> u32 {
+    let creditos: Map<Address, u32> = env
         .storage()
         .persistent()
-        .get(&NOTIFICAS_KEY)
+        .get(&CREDITOS_KEY)
         .unwrap_or(Map::new(&env));
 
-    notificaciones.get(id_notificacion)
+    creditos.get(cuenta).unwrap_or(0)
 }
 
-// AutenticacionAutorizacion
-pub fn autenticar_usuario(env: Env, id_sesion: i32, credenciales: String) {
-    let mut sesiones: Map<i32, String> = env
+// --- Certificados intransferibles (SEP-41) ---
+/// Vincula un documento a la cuenta de su titular. Mientras el documento esté
+/// vigente (ver `ESTADOS_VIGENTES`), el titular tiene su token intransferible;
+/// se quema al revocarlo (o al pasar a cualquier otro estado) y se vuelve a
+/// acuñar si se reactiva.
+/// Requiere la firma del emisor, que debe tener el rol `ROL_EMISOR` vigente.
+/// Panics si el documento no tiene datos de emisión o ya tiene titular.
+pub fn vincular_titular(env: Env, id_documento: i32, titular: Address) {
+    let emisiones: Map<i32, Emision> = env
         .storage()
         .persistent()
-        .get(&SESIONES_KEY)
+        .get(&EMISION_KEY)
         .unwrap_or(Map::new(&env));
+    let emision = match emisiones.get(id_documento) {
+        Some(emision) => emision,
+        None => panic!("Emisión no registrada"),
+    };
+    Self::autorizar_emisor(&env, &emision.emisor);
 
-    sesiones.set(id_sesion, credenciales);
-    env.storage().persistent().set(&SESIONES_KEY, &sesiones);
-}
-
-pub fn asignar_permisos(env: Env, id_sesion: i32, permisos: String) {
-    let mut sesiones: Map<i32, String> = env
+    let mut titulares: Map<i32, Address> = env
         .storage()
         .persistent()
-        .get(&SESIONES_KEY)
+        .get(&TITULARES_KEY)
         .unwrap_or(Map::new(&env));
-
-    if sesiones.contains_key(id_sesion) {
-        sesiones.set(id_sesion, permisos);
-        env.storage().persistent().set(&SESIONES_KEY, &sesiones);
-    } else {
-        panic!("Sesión no encontrada");
+    if titulares.contains_key(id_documento) {
+        panic!("El documento ya tiene titular");
     }
+    titulares.set(id_documento, titular);
+    env.storage().persistent().set(&TITULARES_KEY, &titulares);
+    Self::sincronizar_token(&env, id_documento);
 }
 
-// GeneracionInformes
-pub fn generar_informe(env: Env, id_informe: i32, formato: String) {
-    let mut informes: Map<i32, (u64, String)> = env
+/// Documentos cuyo token tiene `titular`.
+pub fn certificados_de(env: Env, titular: Address) -> Vec<i32> {
+    let posesion: Map<Address, Vec<i32>> = env
         .storage()
         .persistent()
-        .get(&INFORMES_KEY)
+        .get(&POSESION_KEY)
         .unwrap_or(Map::new(&env));
 
-    let fecha_generacion = env.ledger().timestamp();
-    informes.set(id_informe, (fecha_generacion, formato));
-    env.storage().persistent().set(&INFORMES_KEY, &informes);
+    posesion.get(titular).unwrap_or(Vec::new(&env))
 }
 
-pub fn exportar_informe(env: Env, id_informe: i32) -> Option<(u64, String)> {
-    let informes: Map<i32, (u64, String)> = env
+/// Metadatos del token de un documento, o `None` si no está acuñado.
+pub fn metadatos_certificado(env: Env, id_documento: i32) -> Option<MetadatosCertificado> {
+    let titulares: Map<i32, Address> = env
         .storage()
         .persistent()
-        .get(&INFORMES_KEY)
+        .get(&TITULARES_KEY)
         .unwrap_or(Map::new(&env));
+    let titular = titulares.get(id_documento)?;
+    if !Self::certificados_de(env.clone(), titular.clone()).contains(id_documento) {
+        return None;
+    }
+    let (titulo, _, fecha_emision_academica) = Self::obtener_documento(env.clone(), id_documento)?;
+    Some(MetadatosCertificado {
+        id_documento,
+        titular,
+        titulo,
+        fecha_emision_academica,
+    })
+}
 
-    informes.get(id_informe)
+/// SEP-41: número de certificados activos de `id`.
+pub fn balance(env: Env, id: Address) -> i128 {
+    Self::certificados_de(env, id).len() as i128
 }
 
-// ExportacionDatos
-pub fn exportar_datos(env: Env, id_exportacion: i32, formato: String) {
-    let mut exportaciones: Map<i32, String> = env
-        .storage()
-        .persistent()
-        .get(&EXPORTAC_KEY)
-        .unwrap_or(Map::new(&env));
+/// SEP-41: los certificados no tienen fracciones.
+pub fn decimals(_env: Env) -> u32 {
+    0
+}
 
-    exportaciones.set(id_exportacion, formato);
-    env.storage().persistent().set(&EXPORTAC_KEY, &exportaciones);
+pub fn name(env: Env) -> String {
+    String::from_str(&env, NOMBRE_TOKEN)
 }
 
-pub fn validar_exportacion(env: Env, id_exportacion: i32) -> Option<String> {
-    let exportaciones: Map<i32, String> = env
-        .storage()
-        .persistent()
-        .get(&EXPORTAC_KEY)
-        .unwrap_or(Map::new(&env));
+pub fn symbol(env: Env) -> String {
+    String::from_str(&env, SIMBOLO_TOKEN)
+}
 
-    exportaciones.get(id_exportacion)
+/// SEP-41: no hay asignaciones; siempre 0.
+pub fn allowance(_env: Env, _from: Address, _spender: Address) -> i128 {
+    0
 }
 
-// IntegracionSistemaAcademico
-pub fn sincronizar_datos(env: Env, id_integracion: i32, nombre_sistema: String) {
-    let mut integraciones: Map<i32, String> = env
-        .storage()
-        .persistent()
-        .get(&INTEGRA_KEY)
-        .unwrap_or(Map::new(&env));
+/// SEP-41: los certificados son intransferibles.
+/// Panics siempre.
+pub fn transfer(_env: Env, _from: Address, _to: Address, _amount: i128) {
+    panic!("Certificado intransferible");
+}
 
-    integraciones.set(id_integracion, nombre_sistema);
-    env.storage().persistent().set(&INTEGRA_KEY, &integraciones);
+/// Panics siempre.
+pub fn transfer_from(_env: Env, _spender: Address, _from: Address, _to: Address, _amount: i128) {
+    panic!("Certificado intransferible");
 }
 
-pub fn verificar_integracion(env: Env, id_integracion: i32) -> Option<String> {
-    let integraciones: Map<i32, String> = env
-        .storage()
-        .persistent()
-        .get(&INTEGRA_KEY)
-        .unwrap_or(Map::new(&env));
+/// Panics siempre.
+pub fn approve(_env: Env, _from: Address, _spender: Address, _amount: i128, _expiration_ledger: u32) {
+    panic!("Certificado intransferible");
+}
 
-    integraciones.get(id_integracion)
+/// Panics siempre: los tokens sólo se queman al revocar el documento.
+pub fn burn(_env: Env, _from: Address, _amount: i128) {
+    panic!("Certificado intransferible");
+}
+
+/// Panics siempre.
+pub fn burn_from(_env: Env, _spender: Address, _from: Address, _amount: i128) {
+    panic!("Certificado intransferible");
 }
 
+// --- Federación ---
+/// Reconoce otro despliegue de este contrato (el de una universidad del
+/// consorcio) para `verificar_externo`. Requiere la firma del administrador.
+/// Panics si ya estaba reconocido.
+pub fn agregar_par(env: Env, contrato: Address) {
+    Self::admin(&env).require_auth();
+
+    let mut pares = Self::pares_confiables(env.clone());
+    if pares.contains(&contrato) {
+        panic!("Registro ya reconocido");
+    }
+    pares.push_back(contrato);
+    env.storage().persistent().set(&PARES_KEY, &pares);
+}
+
+/// Deja de reconocer un despliegue. Requiere la firma del administrador.
+/// Panics si no estaba reconocido.
+pub fn quitar_par(env: Env, contrato: Address) {
+    Self::admin(&env).require_auth();
+
+    let mut pares = Self::pares_confiables(env.clone());
+    match pares.first_index_of(&contrato) {
+        Some(i) => pares.remove(i),
+        None => panic!("Registro no reconocido"),
+    };
+    env.storage().persistent().set(&PARES_KEY, &pares);
+}
+
+pub fn pares_confiables(env: Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&PARES_KEY)
+        .unwrap_or(Vec::new(&env))
+}
+
+/// Verifica un documento de `contrato`, que debe ser este registro o uno de
+/// sus pares reconocidos. A un par se le pregunta por la interfaz estable
+/// `Verificador`, así que del resultado sólo se sabe si es válido: si no lo
+/// es, el motivo es `RechazadoPorRegistro`, y si la llamada falla (p. ej. el
+/// par no es un registro), `RegistroNoDisponible`. Los datos de emisión
+/// quedan en `None`.
+/// Panics si `contrato` no está reconocido.
+pub fn verificar_externo(env: Env, contrato: Address, id_documento: i32) -> ResultadoVerificacion {
+    if contrato == env.current_contract_address() {
+        return Self::verificar_documento_publico(env, id_documento, None);
+    }
+    if !Self::pares_confiables(env.clone()).contains(&contrato) {
+        panic!("Registro no reconocido");
+    }
+    let motivos = match VerificadorClient::new(&env, &contrato).try_es_valido(&id_documento) {
+        Ok(Ok(true)) => Vec::new(&env),
+        Ok(Ok(false)) => Vec::from_array(&env, [MotivoRechazo::RechazadoPorRegistro]),
+        _ => Vec::from_array(&env, [MotivoRechazo::RegistroNoDisponible]),
+    };
+    ResultadoVerificacion {
+        id_documento,
+        valido: motivos.is_empty(),
+        motivos,
+        estado: None,
+        emisor: None,
+        hash_titular: None,
+        vencimiento: None,
+    }
+}
+
+// --- Instituciones y rotación de claves ---
+/// Registra una institución emisora con su primera dirección de firma,
+/// vigente desde ahora. Requiere la firma del administrador y la de esa
+/// dirección.
+/// Panics si ya existe una institución con el mismo ID.
+pub fn registrar_institucion(env: Env, id_institucion: i32, nombre: String, firmante: Address) {
+    Self::validar_texto(&env, &nombre, MAX_NOMBRE, ErrorValidacion::NombreVacio, ErrorValidacion::NombreDemasiadoLargo);
+    Self::admin(&env).require_auth();
+    Self::autorizar(&env, &firmante);
+
+    let mut instituciones: Map<i32, Institucion> = env
+        .storage()
+        .persistent()
+        .get(&INSTITUC_KEY)
+        .unwrap_or(Map::new(&env));
+
+    if instituciones.contains_key(id_institucion) {
+        panic!("Institución con ese ID ya existe");
+    }
+
+    let mut claves = Vec::new(&env);
+    claves.push_back(ClaveFirma {
+        direccion: firmante,
+        desde: env.ledger().timestamp(),
+        hasta: None,
+    });
+    instituciones.set(id_institucion, Institucion { nombre, claves });
+    env.storage().persistent().set(&INSTITUC_KEY, &instituciones);
+}
+
+/// Sustituye la dirección de firma de una institución. La clave actual queda
+/// vigente hasta ahora, de modo que los documentos que ya emitió siguen
+/// siendo válidos. Requiere la firma de la clave actual y de la nueva.
+/// Panics si la institución no existe.
+pub fn rotar_clave(env: Env, id_institucion: i32, nueva: Address) {
+    Self::sustituir_clave(&env, id_institucion, nueva, false);
+}
+
+/// Sustituye la dirección de firma de una institución sin la firma de la
+/// clave actual, para cuando ésta se ha perdido, se ha visto comprometida o
+/// pertenece a un usuario dado de baja. La clave actual deja de ser vigente
+/// ahora, como en `rotar_clave`. Requiere la firma del administrador y de la
+/// nueva dirección.
+/// Panics si la institución no existe.
+pub fn recuperar_clave(env: Env, id_institucion: i32, nueva: Address) {
+    Self::sustituir_clave(&env, id_institucion, nueva, true);
+}
+
+pub fn obtener_institucion(env: Env, id_institucion: i32) -> Option<Institucion> {
+    let instituciones: Map<i32, Institucion> = env
+        .storage()
+        .persistent()
+        .get(&INSTITUC_KEY)
+        .unwrap_or(Map::new(&env));
+
+    instituciones.get(id_institucion)
+}
+
+/// `true` si `direccion` era una clave de firma vigente de la institución en
+/// `fecha` (`desde <= fecha < hasta`).
+pub fn clave_autorizada(env: Env, id_institucion: i32, direccion: Address, fecha: u64) -> bool {
+    let Some(institucion) = Self::obtener_institucion(env, id_institucion) else {
+        return false;
+    };
+    institucion.claves.iter().any(|clave| {
+        clave.direccion == direccion
+            && clave.desde <= fecha
+            && match clave.hasta {
+                Some(hasta) => fecha < hasta,
+                None => true,
+            }
+    })
+}
+
+/// Asocia a un documento su emisor, el hash del archivo emitido, el de su
+/// titular y su vencimiento (`None` si no vence). Si se indica
+/// `id_institucion`, el emisor debe ser su clave de firma vigente; un
+/// documento sincronizado desde un sistema académico sólo puede emitirlo la
+/// institución de esa integración.
+/// Requiere la firma del emisor, que debe tener el rol `ROL_EMISOR` vigente.
+/// Panics si el documento no existe, ya tiene datos de emisión o el emisor no
+/// tiene el rol o no está autorizado por la institución.
+#[allow(clippy::too_many_arguments)]
+pub fn registrar_emision(
+    env: Env,
+    id_documento: i32,
+    emisor: Address,
+    id_institucion: Option<i32>,
+    hash_documento: BytesN<32>,
+    hash_titular: BytesN<32>,
+    vencimiento: Option<u64>,
+) {
+    if let Some(vencimiento) = vencimiento {
+        if vencimiento <= env.ledger().timestamp() {
+            panic_with_error!(&env, ErrorValidacion::VencimientoPasado);
+        }
+    }
+    Self::autorizar_emisor(&env, &emisor);
+
+    if Self::obtener_documento(env.clone(), id_documento).is_none() {
+        panic!("Documento no encontrado");
+    }
+    let pagada = Self::cobrar(&env, &emisor, |tarifas| tarifas.emision);
+    let fecha = env.ledger().timestamp();
+    if let Some(vinculo) = Self::obtener_id_externo(env.clone(), id_documento) {
+        let integracion = Self::verificar_integracion(env.clone(), vinculo.id_integracion).unwrap();
+        if id_institucion != Some(integracion.id_institucion) {
+            panic!("El documento pertenece a otra institución");
+        }
+    }
+    if let Some(id_institucion) = id_institucion {
+        if !Self::clave_autorizada(env.clone(), id_institucion, emisor.clone(), fecha) {
+            panic!("Emisor no autorizado por la institución");
+        }
+    }
+    let mut vencimiento = vencimiento;
+    if let Some(tipo) = Self::tipo_de(&env, id_documento) {
+        if tipo.politica_firma == PoliticaFirma::Institucional && id_institucion.is_none() {
+            panic!("El tipo de documento exige firma institucional");
+        }
+        if vencimiento.is_none() {
+            vencimiento = tipo.vigencia.map(|vigencia| fecha + vigencia);
+        }
+    }
+
+    let mut emisiones: Map<i32, Emision> = env
+        .storage()
+        .persistent()
+        .get(&EMISION_KEY)
+        .unwrap_or(Map::new(&env));
+
+    if emisiones.contains_key(id_documento) {
+        panic!("Emisión ya registrada");
+    }
+
+    let emision = Emision {
+        emisor,
+        id_institucion,
+        fecha,
+        hash_documento,
+        hash_titular,
+        vencimiento,
+    };
+    emisiones.set(id_documento, emision);
+    env.storage().persistent().set(&EMISION_KEY, &emisiones);
+
+    if let Some(id_institucion) = id_institucion {
+        let mut estadisticas = Self::estadisticas(&env);
+        Self::sumar(&mut estadisticas.por_institucion, id_institucion, 1);
+        env.storage().persistent().set(&ESTADIST_KEY, &estadisticas);
+    }
+    if pagada {
+        Self::extender_ttl(&env, &[DOCUMENTS_KEY, EMISION_KEY, ESTADIST_KEY, USUARIOS_KEY, INSTITUC_KEY, TIPOS_KEY, DOCTIPO_KEY]);
+    }
+}
+
+/// Consulta de sólo lectura para verificadores (p. ej. al escanear el código
+/// QR de un título impreso). Comprueba que el documento exista, que su emisor
+/// siga activo, que su estado sea uno de `ESTADOS_VIGENTES`, que no esté
+/// vencido y, si se presenta
+/// `hash_documento`, que coincida con el emitido. No modifica el registro;
+/// para dejar constancia de la verificación se usa `registrar_verificacion`.
+pub fn verificar_documento_publico(
+    env: Env,
+    id_documento: i32,
+    hash_documento: Option<BytesN<32>>,
+) -> ResultadoVerificacion {
+    let emisiones: Map<i32, Emision> = env
+        .storage()
+        .persistent()
+        .get(&EMISION_KEY)
+        .unwrap_or(Map::new(&env));
+    let emision = emisiones.get(id_documento);
+
+    let estado = Self::obtener_documento(env.clone(), id_documento).map(|(_, estado, _)| estado);
+    let vencimiento = emision.as_ref().and_then(|emision| emision.vencimiento);
+
+    let mut motivos = Vec::new(&env);
+    if emision.is_none() {
+        if let Some(tipo) = Self::tipo_de(&env, id_documento) {
+            if tipo.politica_firma != PoliticaFirma::Ninguna {
+                motivos.push_back(MotivoRechazo::SinFirma);
+            }
+        }
+    }
+    match &estado {
+        Some(estado) if *estado == String::from_str(&env, ESTADO_REVOCADO) => {
+            motivos.push_back(MotivoRechazo::Revocado)
+        }
+        Some(estado) if !Self::estado_vigente(&env, estado) => motivos.push_back(MotivoRechazo::NoVigente),
+        Some(_) => {}
+        None => motivos.push_back(MotivoRechazo::NoEncontrado),
+    }
+    if let Some(emision) = &emision {
+        // La clave institucional ya se comprobó en `registrar_emision`, y una
+        // rotación posterior no la invalida para lo que ya emitió
+        if !Self::emisor_activo(env.clone(), emision.emisor.clone()) {
+            motivos.push_back(MotivoRechazo::EmisorInactivo);
+        }
+    }
+    if let Some(vencimiento) = vencimiento {
+        if env.ledger().timestamp() >= vencimiento {
+            motivos.push_back(MotivoRechazo::Vencido);
+        }
+    }
+    if let Some(hash_documento) = hash_documento {
+        let coincide = match &emision {
+            Some(emision) => emision.hash_documento == hash_documento,
+            None => false,
+        };
+        if !coincide {
+            motivos.push_back(MotivoRechazo::HashNoCoincide);
+        }
+    }
+
+    ResultadoVerificacion {
+        id_documento,
+        valido: motivos.is_empty(),
+        motivos,
+        estado,
+        emisor: emision.as_ref().map(|emision| emision.emisor.clone()),
+        hash_titular: emision.map(|emision| Bytes::from(emision.hash_titular)),
+        vencimiento,
+    }
+}
+
+/// Guarda los campos privados de un documento y el titular que podrá
+/// compartirlos. Requiere la firma del emisor registrado con
+/// `registrar_emision`.
+/// Panics si el documento no tiene datos de emisión.
+pub fn registrar_campos_privados(
+    env: Env,
+    id_documento: i32,
+    titular: Address,
+    campos: Map<soroban_sdk::Symbol, Bytes>,
+) {
+    let emisiones: Map<i32, Emision> = env
+        .storage()
+        .persistent()
+        .get(&EMISION_KEY)
+        .unwrap_or(Map::new(&env));
+
+    let emision = match emisiones.get(id_documento) {
+        Some(emision) => emision,
+        None => panic!("Emisión no registrada"),
+    };
+    Self::autorizar(&env, &emision.emisor);
+
+    let mut privados: Map<i32, CamposPrivados> = env
+        .storage()
+        .persistent()
+        .get(&PRIVADOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    privados.set(id_documento, CamposPrivados { titular, campos });
+    env.storage().persistent().set(&PRIVADOS_KEY, &privados);
+}
+
+/// Permite a `verificador` leer los campos privados del documento hasta la
+/// fecha `hasta` (timestamp Unix). Otorgarlo de nuevo reemplaza el plazo.
+/// Requiere la firma del titular.
+/// Panics si el documento no tiene campos privados o `hasta` ya pasó.
+pub fn otorgar_acceso(env: Env, id_documento: i32, verificador: Address, hasta: u64) {
+    if hasta <= env.ledger().timestamp() {
+        panic_with_error!(&env, ErrorValidacion::VencimientoPasado);
+    }
+    let titular = Self::titular_privado(&env, id_documento);
+    Self::autenticar(&env, &titular);
+
+    let mut accesos: Map<(i32, Address), u64> = env
+        .storage()
+        .persistent()
+        .get(&ACCESOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    accesos.set((id_documento, verificador), hasta);
+    env.storage().persistent().set(&ACCESOS_KEY, &accesos);
+}
+
+/// Retira antes de plazo el acceso de un verificador. Requiere la firma del
+/// titular.
+pub fn revocar_acceso(env: Env, id_documento: i32, verificador: Address) {
+    let titular = Self::titular_privado(&env, id_documento);
+    Self::autenticar(&env, &titular);
+
+    let mut accesos: Map<(i32, Address), u64> = env
+        .storage()
+        .persistent()
+        .get(&ACCESOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    accesos.remove((id_documento, verificador));
+    env.storage().persistent().set(&ACCESOS_KEY, &accesos);
+}
+
+/// Devuelve los campos privados a su titular o a un verificador con acceso
+/// vigente. Requiere la firma de quien consulta.
+/// Panics si el documento no tiene campos privados o el acceso no está
+/// autorizado.
+pub fn obtener_campos_privados(
+    env: Env,
+    id_documento: i32,
+    verificador: Address,
+) -> Map<soroban_sdk::Symbol, Bytes> {
+    Self::autenticar(&env, &verificador);
+
+    let privados: Map<i32, CamposPrivados> = env
+        .storage()
+        .persistent()
+        .get(&PRIVADOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    let privado = match privados.get(id_documento) {
+        Some(privado) => privado,
+        None => panic!("Campos privados no encontrados"),
+    };
+    if privado.titular == verificador {
+        return privado.campos;
+    }
+
+    let accesos: Map<(i32, Address), u64> = env
+        .storage()
+        .persistent()
+        .get(&ACCESOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    match accesos.get((id_documento, verificador)) {
+        Some(hasta) if env.ledger().timestamp() < hasta => privado.campos,
+        _ => panic!("Acceso no autorizado"),
+    }
+}
+
+/// Guarda los compromisos de los campos de un documento para la divulgación
+/// selectiva: por cada campo, `sha256(sal || valor)` con una sal aleatoria de
+/// 32 bytes que sólo conoce el titular. Requiere la firma del emisor
+/// registrado con `registrar_emision`.
+/// Panics si el documento no tiene datos de emisión.
+pub fn registrar_compromisos(
+    env: Env,
+    id_documento: i32,
+    compromisos: Map<soroban_sdk::Symbol, BytesN<32>>,
+) {
+    let emisiones: Map<i32, Emision> = env
+        .storage()
+        .persistent()
+        .get(&EMISION_KEY)
+        .unwrap_or(Map::new(&env));
+
+    let emision = match emisiones.get(id_documento) {
+        Some(emision) => emision,
+        None => panic!("Emisión no registrada"),
+    };
+    Self::autorizar(&env, &emision.emisor);
+
+    let mut todos: Map<i32, Map<soroban_sdk::Symbol, BytesN<32>>> = env
+        .storage()
+        .persistent()
+        .get(&COMPROMI_KEY)
+        .unwrap_or(Map::new(&env));
+
+    todos.set(id_documento, compromisos);
+    env.storage().persistent().set(&COMPROMI_KEY, &todos);
+}
+
+/// Comprueba un único campo divulgado por el titular: `true` si
+/// `sha256(sal || valor)` es el compromiso guardado para `nombre_campo`.
+/// Devuelve `false` si el documento o el campo no tienen compromiso.
+pub fn verificar_campo(
+    env: Env,
+    id_documento: i32,
+    nombre_campo: soroban_sdk::Symbol,
+    valor: String,
+    sal: BytesN<32>,
+) -> bool {
+    let todos: Map<i32, Map<soroban_sdk::Symbol, BytesN<32>>> = env
+        .storage()
+        .persistent()
+        .get(&COMPROMI_KEY)
+        .unwrap_or(Map::new(&env));
+
+    let Some(compromiso) = todos
+        .get(id_documento)
+        .and_then(|compromisos| compromisos.get(nombre_campo))
+    else {
+        return false;
+    };
+
+    // Ningún compromiso se pudo calcular sobre un valor más largo
+    let largo = valor.len() as usize;
+    if largo > MAX_VALOR_CAMPO {
+        return false;
+    }
+    let mut buffer = [0u8; MAX_VALOR_CAMPO];
+    valor.copy_into_slice(&mut buffer[..largo]);
+
+    let mut datos = Bytes::from(sal);
+    datos.extend_from_slice(&buffer[..largo]);
+    env.crypto().sha256(&datos).to_bytes() == compromiso
+}
+
+// --- Enmiendas ---
+/// Corrige el título o la fecha de un documento (p. ej. un cambio de nombre o
+/// una errata) creando una nueva versión enlazada a la anterior, que queda
+/// marcada como reemplazada. El ID del documento no cambia. Requiere la firma
+/// de `responsable`, con las mismas condiciones que `actualizar_estado`.
+/// Panics si el documento no existe, `responsable` no puede cambiarlo o
+/// `cambios` no cambia nada.
+pub fn enmendar_documento(
+    env: Env,
+    id_documento: i32,
+    responsable: Address,
+    cambios: Cambios,
+    motivo: String,
+) -> Version {
+    Self::validar_texto(&env, &motivo, MAX_MOTIVO, ErrorValidacion::MotivoVacio, ErrorValidacion::MotivoDemasiadoLargo);
+    Self::autorizar_responsable(&env, id_documento, &responsable);
+    Self::aplicar_enmienda(&env, id_documento, cambios, motivo)
+}
+
+/// Devuelve la cadena completa de versiones de un documento, de la original a
+/// la vigente. Un documento sin enmiendas tiene una sola versión; uno que no
+/// existe, ninguna.
+pub fn obtener_versiones(env: Env, id_documento: i32) -> Vec<Version> {
+    let todas: Map<i32, Vec<Version>> = env
+        .storage()
+        .persistent()
+        .get(&VERSIONES_KEY)
+        .unwrap_or(Map::new(&env));
+
+    if let Some(versiones) = todas.get(id_documento) {
+        return versiones;
+    }
+    let mut versiones = Vec::new(&env);
+    if let Some((titulo, _, fecha_emision_academica)) = Self::obtener_documento(env.clone(), id_documento) {
+        versiones.push_back(Version {
+            numero: 1,
+            anterior: None,
+            titulo,
+            fecha_emision_academica,
+            motivo: String::from_str(&env, ""),
+            registrada: 0,
+            reemplazada: false,
+        });
+    }
+    versiones
+}
+
+// --- Atributos ---
+/// Fija (o elimina, con `valor` a `None`) un atributo de un documento, como
+/// el programa, los créditos, la mención o el idioma, y anota el cambio en el
+/// historial de atributos. Requiere la firma de `responsable`, con las
+/// mismas condiciones que `actualizar_estado`.
+/// Panics si el documento no existe, `responsable` no puede cambiarlo, el
+/// valor supera `MAX_VALOR_CAMPO` bytes o el documento ya tiene
+/// `MAX_ATRIBUTOS` atributos.
+pub fn establecer_atributo(
+    env: Env,
+    id_documento: i32,
+    responsable: Address,
+    clave: soroban_sdk::Symbol,
+    valor: Option<String>,
+) {
+    if let Some(valor) = &valor {
+        if valor.len() as usize > MAX_VALOR_CAMPO {
+            panic_with_error!(&env, ErrorValidacion::ValorDemasiadoLargo);
+        }
+    }
+    if Self::obtener_documento(env.clone(), id_documento).is_none() {
+        panic!("Documento no encontrado");
+    }
+    Self::autorizar_responsable(&env, id_documento, &responsable);
+
+    let mut atributos = Self::obtener_atributos(env.clone(), id_documento);
+    let anterior = atributos.get(clave.clone());
+    match &valor {
+        Some(valor) => {
+            if anterior.is_none() && atributos.len() >= MAX_ATRIBUTOS {
+                panic!("Demasiados atributos");
+            }
+            atributos.set(clave.clone(), valor.clone());
+        }
+        None => {
+            atributos.remove(clave.clone());
+        }
+    }
+    if anterior == valor {
+        return;
+    }
+
+    let mut todos: Map<i32, Map<soroban_sdk::Symbol, String>> = env
+        .storage()
+        .persistent()
+        .get(&ATRIBUTOS_KEY)
+        .unwrap_or(Map::new(&env));
+    todos.set(id_documento, atributos);
+    env.storage().persistent().set(&ATRIBUTOS_KEY, &todos);
+
+    let mut cambios = Self::obtener_cambios_atributos(env.clone(), id_documento);
+    cambios.push_back(CambioAtributo {
+        clave,
+        anterior,
+        valor,
+        fecha: env.ledger().timestamp(),
+    });
+    let mut historial: Map<i32, Vec<CambioAtributo>> = env
+        .storage()
+        .persistent()
+        .get(&CAMBIOATR_KEY)
+        .unwrap_or(Map::new(&env));
+    historial.set(id_documento, cambios);
+    env.storage().persistent().set(&CAMBIOATR_KEY, &historial);
+}
+
+pub fn obtener_atributos(env: Env, id_documento: i32) -> Map<soroban_sdk::Symbol, String> {
+    let todos: Map<i32, Map<soroban_sdk::Symbol, String>> = env
+        .storage()
+        .persistent()
+        .get(&ATRIBUTOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    todos.get(id_documento).unwrap_or(Map::new(&env))
+}
+
+/// Historial de cambios de atributos de un documento, del más antiguo al más
+/// reciente.
+pub fn obtener_cambios_atributos(env: Env, id_documento: i32) -> Vec<CambioAtributo> {
+    let historial: Map<i32, Vec<CambioAtributo>> = env
+        .storage()
+        .persistent()
+        .get(&CAMBIOATR_KEY)
+        .unwrap_or(Map::new(&env));
+
+    historial.get(id_documento).unwrap_or(Vec::new(&env))
+}
+
+/// Cambia el estado de un documento. Requiere la firma de `responsable`,
+/// que debe tener un perfil de usuario activo; si el documento tiene datos
+/// de emisión, `responsable` tiene que ser su emisor, la clave vigente de su
+/// institución o el administrador (p. ej. para revocar lo que firmó un emisor
+/// dado de baja o una clave ya rotada).
+/// Panics si el documento no existe o `responsable` no puede cambiarlo.
+pub fn actualizar_estado(env: Env, id_documento: i32, responsable: Address, nuevo_estado: String) {
+    Self::validar_texto(&env, &nuevo_estado, MAX_ESTADO, ErrorValidacion::EstadoVacio, ErrorValidacion::EstadoDemasiadoLargo);
+    Self::autorizar_responsable(&env, id_documento, &responsable);
+    Self::cambiar_estado(&env, id_documento, nuevo_estado);
+}
+
+// GestionUsuarios
+/// Da de alta el perfil de la dirección `direccion` con el rol `rol`, sin
+/// vencimiento. Requiere la firma del administrador.
+/// Panics si la dirección ya tiene perfil.
+pub fn crear_usuario(
+    env: Env,
+    direccion: Address,
+    nombre: Option<String>,
+    hash_email: Option<BytesN<32>>,
+    rol: String,
+) {
+    if let Some(nombre) = &nombre {
+        Self::validar_texto(&env, nombre, MAX_NOMBRE, ErrorValidacion::NombreVacio, ErrorValidacion::NombreDemasiadoLargo);
+    }
+    Self::validar_texto(&env, &rol, MAX_ROL, ErrorValidacion::RolVacio, ErrorValidacion::RolDemasiadoLargo);
+    Self::admin(&env).require_auth();
+
+    let mut usuarios: Map<Address, Usuario> = env
+        .storage()
+        .persistent()
+        .get(&USUARIOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    if usuarios.contains_key(direccion.clone()) {
+        panic!("Usuario ya existe");
+    }
+
+    let usuario = Usuario {
+        nombre,
+        hash_email: hash_email.map(Bytes::from),
+        roles: vec![&env, ConcesionRol { rol, hasta: None }],
+        activo: true,
+    };
+    usuarios.set(direccion.clone(), usuario);
+    env.storage().persistent().set(&USUARIOS_KEY, &usuarios);
+    Self::anotar_usuario(&env, &direccion, AccionUsuario::Creado);
+}
+
+/// Concede un rol a un usuario hasta `hasta` (momento del ledger, exclusivo)
+/// o sin vencimiento si es `None`, p. ej. derechos de emisión para personal
+/// temporal. Si ya tenía el rol, se reemplaza su vencimiento. Requiere la
+/// firma del administrador.
+/// Panics si la dirección no tiene perfil o `hasta` ya pasó.
+pub fn asignar_rol(env: Env, direccion: Address, nuevo_rol: String, hasta: Option<u64>) {
+    Self::validar_texto(&env, &nuevo_rol, MAX_ROL, ErrorValidacion::RolVacio, ErrorValidacion::RolDemasiadoLargo);
+    Self::admin(&env).require_auth();
+    if let Some(hasta) = hasta {
+        if hasta <= env.ledger().timestamp() {
+            panic_with_error!(&env, ErrorValidacion::VencimientoPasado);
+        }
+    }
+
+    let mut usuario = Self::usuario_existente(&env, &direccion);
+    let concesion = ConcesionRol { rol: nuevo_rol.clone(), hasta };
+    match usuario.roles.iter().position(|actual| actual.rol == nuevo_rol) {
+        Some(i) => usuario.roles.set(i as u32, concesion),
+        None => usuario.roles.push_back(concesion),
+    }
+    Self::guardar_usuario(&env, &direccion, usuario);
+    Self::anotar_usuario(&env, &direccion, AccionUsuario::RolAsignado);
+}
+
+/// Retira un rol a un usuario. Requiere la firma del administrador.
+/// Panics si la dirección no tiene perfil o no tiene ese rol.
+pub fn revocar_rol(env: Env, direccion: Address, rol: String) {
+    Self::admin(&env).require_auth();
+
+    let mut usuario = Self::usuario_existente(&env, &direccion);
+    match usuario.roles.iter().position(|actual| actual.rol == rol) {
+        Some(i) => usuario.roles.remove(i as u32),
+        None => panic!("Rol no asignado"),
+    };
+    Self::guardar_usuario(&env, &direccion, usuario);
+    Self::anotar_usuario(&env, &direccion, AccionUsuario::RolRevocado);
+}
+
+pub fn obtener_usuario(env: Env, direccion: Address) -> Option<Usuario> {
+    let usuarios: Map<Address, Usuario> = env
+        .storage()
+        .persistent()
+        .get(&USUARIOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    usuarios.get(direccion)
+}
+
+/// Desactiva un usuario (p. ej. un empleado que deja la institución): sus
+/// comprobaciones de permisos fallan hasta que se reactive, y también si se
+/// elimina su perfil. Requiere la firma del administrador.
+/// Panics si el usuario no existe, ya está inactivo o es el administrador.
+pub fn desactivar_usuario(env: Env, direccion: Address) {
+    let admin = Self::admin(&env);
+    admin.require_auth();
+    if direccion == admin {
+        panic!("No se puede desactivar al administrador");
+    }
+
+    let mut usuario = Self::usuario_existente(&env, &direccion);
+    if !usuario.activo {
+        panic!("Usuario ya inactivo");
+    }
+    usuario.activo = false;
+    Self::guardar_usuario(&env, &direccion, usuario);
+    Self::anotar_usuario(&env, &direccion, AccionUsuario::Desactivado);
+}
+
+/// Reactiva un usuario desactivado. Requiere la firma del administrador.
+/// Panics si el usuario no existe o ya está activo.
+pub fn reactivar_usuario(env: Env, direccion: Address) {
+    Self::admin(&env).require_auth();
+
+    let mut usuario = Self::usuario_existente(&env, &direccion);
+    if usuario.activo {
+        panic!("Usuario ya activo");
+    }
+    usuario.activo = true;
+    Self::guardar_usuario(&env, &direccion, usuario);
+    Self::anotar_usuario(&env, &direccion, AccionUsuario::Reactivado);
+}
+
+/// Borra el perfil de un usuario. Su bitácora se conserva. Como las
+/// operaciones del personal exigen un perfil activo, la dirección sigue sin
+/// poder realizarlas, igual que si estuviera desactivada. Requiere la firma
+/// del administrador.
+/// Panics si el usuario no existe o es el administrador.
+pub fn eliminar_usuario(env: Env, direccion: Address) {
+    let admin = Self::admin(&env);
+    admin.require_auth();
+    if direccion == admin {
+        panic!("No se puede eliminar al administrador");
+    }
+
+    let mut usuarios: Map<Address, Usuario> = env
+        .storage()
+        .persistent()
+        .get(&USUARIOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    if usuarios.remove(direccion.clone()).is_none() {
+        panic!("Usuario no encontrado");
+    }
+    env.storage().persistent().set(&USUARIOS_KEY, &usuarios);
+    Self::anotar_usuario(&env, &direccion, AccionUsuario::Eliminado);
+}
+
+/// `true` si el usuario existe, está activo y tiene el rol `rol` vigente en
+/// el ledger actual.
+pub fn tiene_rol(env: Env, direccion: Address, rol: String) -> bool {
+    Self::roles_vigentes(env, direccion)
+        .iter()
+        .any(|concesion| concesion.rol == rol)
+}
+
+/// Roles vigentes de un usuario en el ledger actual, con su vencimiento.
+/// Vacío si el usuario no existe o está inactivo.
+pub fn roles_vigentes(env: Env, direccion: Address) -> Vec<ConcesionRol> {
+    let mut vigentes = Vec::new(&env);
+    let Some(usuario) = Self::obtener_usuario(env.clone(), direccion) else {
+        return vigentes;
+    };
+    if !usuario.activo {
+        return vigentes;
+    }
+    let ahora = env.ledger().timestamp();
+    for concesion in usuario.roles.iter() {
+        let vigente = match concesion.hasta {
+            Some(hasta) => ahora < hasta,
+            None => true,
+        };
+        if vigente {
+            vigentes.push_back(concesion);
+        }
+    }
+    vigentes
+}
+
+/// Cambios de un usuario, del más antiguo al más reciente.
+pub fn obtener_bitacora_usuario(env: Env, direccion: Address) -> Vec<CambioUsuario> {
+    let bitacora: Map<Address, Vec<CambioUsuario>> = env
+        .storage()
+        .persistent()
+        .get(&BITACORA_KEY)
+        .unwrap_or(Map::new(&env));
+
+    bitacora.get(direccion).unwrap_or(Vec::new(&env))
+}
+
+// ConfiguracionNotificaciones
+/// Suscribe a `suscriptor` a un tipo de evento de un documento (o de todos, si
+/// `id_documento` es `None`). Los avisos se emiten como eventos del contrato y
+/// los entrega el relé fuera de la cadena (`tools/notificador`) por `canal`.
+/// Requiere la firma del suscriptor y, para seguir todos los documentos, la
+/// del administrador.
+/// Panics si ya existe una suscripción con el mismo ID, si el documento no
+/// existe o si el documento o el suscriptor ya tienen el máximo de
+/// suscripciones (`MAX_SUSCRIPCIONES_DOCUMENTO`, `MAX_SUSCRIPCIONES_SUSCRIPTOR`).
+pub fn configurar_notificacion(
+    env: Env,
+    id_notificacion: i32,
+    suscriptor: Address,
+    id_documento: Option<i32>,
+    evento: TipoEvento,
+    canal: String,
+) {
+    Self::validar_canal(&env, &canal);
+    Self::autenticar(&env, &suscriptor);
+    match id_documento {
+        Some(id_documento) => {
+            if Self::obtener_documento(env.clone(), id_documento).is_none() {
+                panic!("Documento no encontrado");
+            }
+        }
+        None => {
+            let admin = Self::admin(&env);
+            if admin != suscriptor {
+                admin.require_auth();
+            }
+        }
+    }
+    if Self::suscripcion(&env, id_notificacion).is_some() {
+        panic!("Notificación con ese ID ya existe");
+    }
+
+    let mut suscripciones = Self::suscripciones(&env, id_documento);
+    if suscripciones.len() >= MAX_SUSCRIPCIONES_DOCUMENTO {
+        panic!("Demasiadas suscripciones al documento");
+    }
+    let propias = Self::suscripciones_de(&env, &suscriptor);
+    if propias >= MAX_SUSCRIPCIONES_SUSCRIPTOR {
+        panic!("Demasiadas suscripciones del suscriptor");
+    }
+
+    env.storage().persistent().set(&(SUSCRIPT_KEY, suscriptor.clone()), &(propias + 1));
+    let suscripcion = Suscripcion {
+        suscriptor,
+        id_documento,
+        evento,
+        canal,
+    };
+    suscripciones.set(id_notificacion, suscripcion);
+    env.storage().persistent().set(&(NOTIFICAS_KEY, id_documento), &suscripciones);
+    env.storage().persistent().set(&(SUSCRIPC_KEY, id_notificacion), &id_documento);
+}
+
+/// Cancela una suscripción. Requiere la firma del suscriptor.
+pub fn cancelar_notificacion(env: Env, id_notificacion: i32) {
+    let Some(suscripcion) = Self::suscripcion(&env, id_notificacion) else {
+        panic!("Notificación no encontrada");
+    };
+    Self::autenticar(&env, &suscripcion.suscriptor);
+
+    let mut suscripciones = Self::suscripciones(&env, suscripcion.id_documento);
+    suscripciones.remove(id_notificacion);
+    env.storage().persistent().set(&(NOTIFICAS_KEY, suscripcion.id_documento), &suscripciones);
+    env.storage().persistent().remove(&(SUSCRIPC_KEY, id_notificacion));
+    let propias = Self::suscripciones_de(&env, &suscripcion.suscriptor);
+    env.storage().persistent().set(&(SUSCRIPT_KEY, suscripcion.suscriptor), &(propias - 1));
+}
+
+/// Emite un aviso de prueba para una suscripción y la devuelve. Requiere la
+/// firma del suscriptor.
+/// Devuelve `None` (sin emitir nada) si la suscripción no existe.
+pub fn enviar_notificacion(env: Env, id_notificacion: i32) -> Option<Suscripcion> {
+    let suscripcion = Self::suscripcion(&env, id_notificacion)?;
+    Self::autenticar(&env, &suscripcion.suscriptor);
+    let id_documento = suscripcion.id_documento.unwrap_or(0);
+    let estado = Self::obtener_documento(env.clone(), id_documento)
+        .map(|(_, estado, _)| estado)
+        .unwrap_or(String::from_str(&env, ""));
+    Self::emitir_notificacion(&env, id_notificacion, &suscripcion, id_documento, &estado);
+    Some(suscripcion)
+}
+
+// AutenticacionAutorizacion
+pub fn autenticar_usuario(env: Env, id_sesion: i32, credenciales: String) {
+    let mut sesiones: Map<i32, String> = env
+        .storage()
+        .persistent()
+        .get(&SESIONES_KEY)
+        .unwrap_or(Map::new(&env));
+
+    sesiones.set(id_sesion, credenciales);
+    env.storage().persistent().set(&SESIONES_KEY, &sesiones);
+}
+
+pub fn asignar_permisos(env: Env, id_sesion: i32, permisos: String) {
+    let mut sesiones: Map<i32, String> = env
+        .storage()
+        .persistent()
+        .get(&SESIONES_KEY)
+        .unwrap_or(Map::new(&env));
+
+    if sesiones.contains_key(id_sesion) {
+        sesiones.set(id_sesion, permisos);
+        env.storage().persistent().set(&SESIONES_KEY, &sesiones);
+    } else {
+        panic!("Sesión no encontrada");
+    }
+}
+
+// GeneracionInformes
+/// Calcula un informe a partir de los contadores agregados y lo guarda con
+/// `id_informe` para poder exportarlo después.
+/// Los conteos por mes y las verificaciones se limitan a los meses que cubre
+/// el `rango`; los conteos por estado y por institución reflejan el estado
+/// actual de todo el registro. Requiere la firma del administrador.
+/// Panics si `rango.desde` es posterior a `rango.hasta`.
+pub fn generar_informe(env: Env, id_informe: i32, tipo: TipoInforme, rango: Rango) -> Informe {
+    if rango.desde > rango.hasta {
+        panic!("Rango de fechas inválido");
+    }
+    Self::autorizar(&env, &Self::admin(&env));
+
+    let mut informes: Map<i32, Informe> = env
+        .storage()
+        .persistent()
+        .get(&INFORMES_KEY)
+        .unwrap_or(Map::new(&env));
+
+    let estadisticas = Self::estadisticas(&env);
+    let (mes_desde, mes_hasta) = (Self::mes(rango.desde), Self::mes(rango.hasta));
+    let mut informe = Informe {
+        tipo,
+        rango,
+        fecha_generacion: env.ledger().timestamp(),
+        total_documentos: estadisticas.total_documentos,
+        por_estado: Map::new(&env),
+        por_mes: Map::new(&env),
+        verificaciones: 0,
+        por_institucion: Map::new(&env),
+    };
+
+    if matches!(tipo, TipoInforme::General | TipoInforme::PorEstado) {
+        for (estado, total) in estadisticas.por_estado.iter() {
+            if total > 0 {
+                informe.por_estado.set(estado, total);
+            }
+        }
+    }
+    if matches!(tipo, TipoInforme::General | TipoInforme::PorMes) {
+        for (mes, total) in estadisticas.por_mes.iter() {
+            if mes >= mes_desde && mes <= mes_hasta {
+                informe.por_mes.set(mes, total);
+            }
+        }
+    }
+    if matches!(tipo, TipoInforme::General | TipoInforme::Verificaciones) {
+        for (mes, total) in estadisticas.verificaciones_por_mes.iter() {
+            if mes >= mes_desde && mes <= mes_hasta {
+                informe.verificaciones += total;
+            }
+        }
+    }
+    if matches!(tipo, TipoInforme::General | TipoInforme::PorInstitucion) {
+        informe.por_institucion = estadisticas.por_institucion;
+    }
+
+    informes.set(id_informe, informe.clone());
+    env.storage().persistent().set(&INFORMES_KEY, &informes);
+    informe
+}
+
+pub fn exportar_informe(env: Env, id_informe: i32) -> Option<Informe> {
+    let informes: Map<i32, Informe> = env
+        .storage()
+        .persistent()
+        .get(&INFORMES_KEY)
+        .unwrap_or(Map::new(&env));
+
+    informes.get(id_informe)
+}
+
+// ExportacionDatos
+/// Registra una exportación del registro hecha fuera de la cadena (ver `tools/exportador`):
+/// el formato (p. ej. `csv`, hasta `MAX_FORMATO` bytes), el hash SHA-256 del
+/// contenido exportado y el número de filas. Requiere la firma del administrador.
+/// Panics si ya existe una exportación con el mismo ID.
+pub fn exportar_datos(env: Env, id_exportacion: i32, formato: String, hash: BytesN<32>, filas: u32) {
+    Self::validar_texto(&env, &formato, MAX_FORMATO, ErrorValidacion::FormatoVacio, ErrorValidacion::FormatoDemasiadoLargo);
+    Self::autorizar(&env, &Self::admin(&env));
+
+    let mut exportaciones: Map<i32, Exportacion> = env
+        .storage()
+        .persistent()
+        .get(&EXPORTAC_KEY)
+        .unwrap_or(Map::new(&env));
+
+    if exportaciones.contains_key(id_exportacion) {
+        panic!("Exportación con ese ID ya existe");
+    }
+
+    let exportacion = Exportacion {
+        formato,
+        hash,
+        filas,
+        fecha: env.ledger().timestamp(),
+    };
+    exportaciones.set(id_exportacion, exportacion);
+    env.storage().persistent().set(&EXPORTAC_KEY, &exportaciones);
+}
+
+pub fn validar_exportacion(env: Env, id_exportacion: i32) -> Option<Exportacion> {
+    let exportaciones: Map<i32, Exportacion> = env
+        .storage()
+        .persistent()
+        .get(&EXPORTAC_KEY)
+        .unwrap_or(Map::new(&env));
+
+    exportaciones.get(id_exportacion)
+}
+
+// IntegracionSistemaAcademico
+/// Registra un sistema académico (SIS) con el operador que firmará sus
+/// sincronizaciones y la institución cuyos documentos puede sincronizar.
+/// Requiere la firma del operador y de la clave vigente de la institución.
+/// Panics si ya existe una integración con el mismo ID o la institución no
+/// existe.
+pub fn registrar_integracion(
+    env: Env,
+    id_integracion: i32,
+    nombre_sistema: String,
+    operador: Address,
+    id_institucion: i32,
+) {
+    Self::validar_texto(&env, &nombre_sistema, MAX_SISTEMA, ErrorValidacion::SistemaVacio, ErrorValidacion::SistemaDemasiadoLargo);
+    Self::autorizar(&env, &operador);
+    let institucion = match Self::obtener_institucion(env.clone(), id_institucion) {
+        Some(institucion) => institucion,
+        None => panic!("Institución no encontrada"),
+    };
+    let vigente = institucion.claves.get(institucion.claves.len() - 1).unwrap();
+    if vigente.direccion != operador {
+        Self::autorizar(&env, &vigente.direccion);
+    }
+
+    let mut integraciones: Map<i32, Integracion> = env
+        .storage()
+        .persistent()
+        .get(&INTEGRA_KEY)
+        .unwrap_or(Map::new(&env));
+
+    if integraciones.contains_key(id_integracion) {
+        panic!("Integración con ese ID ya existe");
+    }
+
+    let integracion = Integracion {
+        nombre_sistema,
+        operador,
+        id_institucion,
+        ultimo_cursor: 0,
+        ultima_sincronizacion: 0,
+        total_sincronizados: 0,
+    };
+    integraciones.set(id_integracion, integracion);
+    env.storage().persistent().set(&INTEGRA_KEY, &integraciones);
+}
+
+pub fn verificar_integracion(env: Env, id_integracion: i32) -> Option<Integracion> {
+    let integraciones: Map<i32, Integracion> = env
+        .storage()
+        .persistent()
+        .get(&INTEGRA_KEY)
+        .unwrap_or(Map::new(&env));
+
+    integraciones.get(id_integracion)
+}
+
+/// Crea o actualiza el documento que corresponde a `id_externo` en el sistema
+/// académico. Si el registro externo ya está vinculado, se actualiza el
+/// estado del documento vinculado y los cambios de título o fecha se aplican
+/// como una enmienda (ver `enmendar_documento`); si no, se registra
+/// `id_documento` y se vincula. Repetir la misma llamada no tiene efecto
+/// adicional. Devuelve el ID del documento. Requiere la firma del operador y,
+/// si el documento ya tiene datos de emisión, la del emisor.
+/// Panics si la integración no existe o `cursor` es anterior al último
+/// sincronizado.
+///
+/// # Arguments
+///
+/// * `id_integracion` - La integración registrada (i32).
+/// * `id_externo` - El ID del alumno/curso en el sistema académico (String).
+/// * `id_documento` - El ID a usar si el documento aún no existe (i32).
+/// * `titulo`, `estado`, `fecha_emision_academica` - Los datos del documento.
+/// * `cursor` - La marca de avance del SIS para este cambio (u64).
+#[allow(clippy::too_many_arguments)]
+pub fn sincronizar_documento(
+    env: Env,
+    id_integracion: i32,
+    id_externo: String,
+    id_documento: i32,
+    titulo: String,
+    estado: String,
+    fecha_emision_academica: u64,
+    cursor: u64,
+) -> i32 {
+    Self::validar_texto(&env, &id_externo, MAX_ID_EXTERNO, ErrorValidacion::IdExternoVacio, ErrorValidacion::IdExternoDemasiadoLargo);
+    Self::validar_documento(&env, &titulo, &estado, fecha_emision_academica);
+
+    let mut integraciones: Map<i32, Integracion> = env
+        .storage()
+        .persistent()
+        .get(&INTEGRA_KEY)
+        .unwrap_or(Map::new(&env));
+
+    let mut integracion = match integraciones.get(id_integracion) {
+        Some(integracion) => integracion,
+        None => panic!("Integración no encontrada"),
+    };
+    Self::autorizar(&env, &integracion.operador);
+    if cursor < integracion.ultimo_cursor {
+        panic!("Cursor anterior a la última sincronización");
+    }
+
+    let mut externos: Map<IdExterno, i32> = env
+        .storage()
+        .persistent()
+        .get(&EXTERNOS_KEY)
+        .unwrap_or(Map::new(&env));
+    let clave = IdExterno {
+        id_integracion,
+        id_externo,
+    };
+
+    let id_documento = match externos.get(clave.clone()) {
+        Some(id_vinculado) => {
+            let (titulo_actual, estado_actual, fecha_actual) =
+                Self::obtener_documento(env.clone(), id_vinculado).unwrap();
+            let enmienda = titulo_actual != titulo || fecha_actual != fecha_emision_academica;
+            if estado_actual != estado || enmienda {
+                if let Some(emision) = Self::emision(&env, id_vinculado) {
+                    if !Self::responde_de_emision(&env, &emision, &integracion.operador) {
+                        Self::autorizar(&env, &emision.emisor);
+                    }
+                }
+            }
+            if estado_actual != estado {
+                Self::cambiar_estado(&env, id_vinculado, estado.clone());
+            }
+            if enmienda {
+                let cambios = Cambios {
+                    titulo: Some(titulo).filter(|titulo| *titulo != titulo_actual),
+                    fecha_emision_academica: Some(fecha_emision_academica).filter(|fecha| *fecha != fecha_actual),
+                };
+                Self::aplicar_enmienda(&env, id_vinculado, cambios, String::from_str(&env, MOTIVO_SINCRONIZACION));
+            }
+            id_vinculado
+        }
+        None => {
+            Self::alta_documento(&env, id_documento, titulo, estado, fecha_emision_academica);
+            let mut vinculos: Map<i32, IdExterno> = env
+                .storage()
+                .persistent()
+                .get(&DOCEXTER_KEY)
+                .unwrap_or(Map::new(&env));
+            vinculos.set(id_documento, clave.clone());
+            env.storage().persistent().set(&DOCEXTER_KEY, &vinculos);
+            externos.set(clave, id_documento);
+            env.storage().persistent().set(&EXTERNOS_KEY, &externos);
+            integracion.total_sincronizados += 1;
+            id_documento
+        }
+    };
+
+    integracion.ultimo_cursor = cursor;
+    integracion.ultima_sincronizacion = env.ledger().timestamp();
+    integraciones.set(id_integracion, integracion);
+    env.storage().persistent().set(&INTEGRA_KEY, &integraciones);
+    id_documento
+}
+
+/// Devuelve el documento vinculado a un registro del sistema académico.
+pub fn buscar_por_id_externo(env: Env, id_integracion: i32, id_externo: String) -> Option<i32> {
+    let externos: Map<IdExterno, i32> = env
+        .storage()
+        .persistent()
+        .get(&EXTERNOS_KEY)
+        .unwrap_or(Map::new(&env));
+
+    externos.get(IdExterno {
+        id_integracion,
+        id_externo,
+    })
+}
+
+/// Devuelve el registro del sistema académico vinculado a un documento.
+pub fn obtener_id_externo(env: Env, id_documento: i32) -> Option<IdExterno> {
+    let vinculos: Map<i32, IdExterno> = env
+        .storage()
+        .persistent()
+        .get(&DOCEXTER_KEY)
+        .unwrap_or(Map::new(&env));
+
+    vinculos.get(id_documento)
+}
+
+}
+
+#[contractimpl]
+impl Verificador for DocumentosContract {
+    fn es_valido(env: Env, id_documento: i32) -> bool {
+        let resultado = Self::verificar_documento_publico(env, id_documento, None);
+        resultado.valido && resultado.emisor.is_some()
+    }
+
+    fn titular_tiene_credencial(env: Env, titular: Address, id_tipo: i32) -> bool {
+        Self::certificados_de(env.clone(), titular).iter().any(|id_documento| {
+            let del_tipo = match Self::obtener_documento_tipado(env.clone(), id_documento) {
+                Some(tipado) => tipado.id_tipo == id_tipo,
+                None => false,
+            };
+            del_tipo && Self::es_valido(env.clone(), id_documento)
+        })
+    }
+}
+
+impl DocumentosContract {
+    /// Emite un aviso por cada suscripción a `evento` sobre `id_documento`.
+    /// Sólo lee las suscripciones del documento y las de todos los
+    /// documentos, ambas acotadas por `MAX_SUSCRIPCIONES_DOCUMENTO`.
+    fn notificar(env: &Env, id_documento: i32, evento: TipoEvento, estado: &String) {
+        for grupo in [Some(id_documento), None] {
+            for (id_notificacion, suscripcion) in Self::suscripciones(env, grupo).iter() {
+                if suscripcion.evento == evento {
+                    Self::emitir_notificacion(env, id_notificacion, &suscripcion, id_documento, estado);
+                }
+            }
+        }
+    }
+
+    /// Suscripciones a `id_documento` (`None`: las que siguen a todos).
+    fn suscripciones(env: &Env, id_documento: Option<i32>) -> Map<i32, Suscripcion> {
+        env.storage()
+            .persistent()
+            .get(&(NOTIFICAS_KEY, id_documento))
+            .unwrap_or(Map::new(env))
+    }
+
+    fn suscripcion(env: &Env, id_notificacion: i32) -> Option<Suscripcion> {
+        let id_documento: Option<i32> = env.storage().persistent().get(&(SUSCRIPC_KEY, id_notificacion))?;
+        Self::suscripciones(env, id_documento).get(id_notificacion)
+    }
+
+    /// Número de suscripciones activas de `suscriptor`.
+    fn suscripciones_de(env: &Env, suscriptor: &Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&(SUSCRIPT_KEY, suscriptor.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Falla si `canal` está vacío, es demasiado largo o no usa uno de los
+    /// esquemas de `CANALES` seguido de un destino.
+    fn validar_canal(env: &Env, canal: &String) {
+        Self::validar_texto(env, canal, MAX_CANAL, ErrorValidacion::CanalVacio, ErrorValidacion::CanalDemasiadoLargo);
+        let mut buffer = [0u8; MAX_CANAL as usize];
+        let largo = canal.len() as usize;
+        canal.copy_into_slice(&mut buffer[..largo]);
+        let canal = &buffer[..largo];
+        let valido = CANALES
+            .iter()
+            .any(|esquema| canal.len() > esquema.len() && canal.starts_with(esquema.as_bytes()));
+        if !valido {
+            panic_with_error!(env, ErrorValidacion::CanalNoValido);
+        }
+    }
+
+    fn emitir_notificacion(
+        env: &Env,
+        id_notificacion: i32,
+        suscripcion: &Suscripcion,
+        id_documento: i32,
+        estado: &String,
+    ) {
+        env.events().publish(
+            (symbol_short!("notif"), suscripcion.suscriptor.clone(), id_documento),
+            Notificacion {
+                id_notificacion,
+                evento: suscripcion.evento,
+                canal: suscripcion.canal.clone(),
+                estado: estado.clone(),
+            },
+        );
+    }
+
+    /// Falla con `vacio` o `largo` si `texto` está vacío o supera `max` bytes.
+    fn validar_texto(env: &Env, texto: &String, max: u32, vacio: ErrorValidacion, largo: ErrorValidacion) {
+        if texto.is_empty() {
+            panic_with_error!(env, vacio);
+        }
+        if texto.len() > max {
+            panic_with_error!(env, largo);
+        }
+    }
+
+    /// Falla si la fecha es 0 o posterior al ledger más `TOLERANCIA_FECHA`.
+    fn validar_fecha(env: &Env, fecha: u64) {
+        if fecha == 0 {
+            panic_with_error!(env, ErrorValidacion::FechaNula);
+        }
+        if fecha > env.ledger().timestamp().saturating_add(TOLERANCIA_FECHA) {
+            panic_with_error!(env, ErrorValidacion::FechaFutura);
+        }
+    }
+
+    fn validar_documento(env: &Env, titulo: &String, estado: &String, fecha: u64) {
+        Self::validar_texto(env, titulo, MAX_TITULO, ErrorValidacion::TituloVacio, ErrorValidacion::TituloDemasiadoLargo);
+        Self::validar_texto(env, estado, MAX_ESTADO, ErrorValidacion::EstadoVacio, ErrorValidacion::EstadoDemasiadoLargo);
+        Self::validar_fecha(env, fecha);
+    }
+
+    /// Exige la firma de `direccion` y que tenga un perfil de usuario activo.
+    /// Es la comprobación de las operaciones del personal de la institución:
+    /// un usuario desactivado o eliminado no la supera.
+    fn autorizar(env: &Env, direccion: &Address) {
+        direccion.require_auth();
+        if !Self::usuario_existente(env, direccion).activo {
+            panic!("Usuario inactivo");
+        }
+    }
+
+    /// Como `autorizar`, y además que `emisor` tenga el rol `ROL_EMISOR`
+    /// vigente en el ledger actual.
+    fn autorizar_emisor(env: &Env, emisor: &Address) {
+        Self::autorizar(env, emisor);
+        if !Self::tiene_rol(env.clone(), emisor.clone(), String::from_str(env, ROL_EMISOR)) {
+            panic!("Emisor sin rol de emisión vigente");
+        }
+    }
+
+    /// Exige la firma de `direccion` para las operaciones abiertas a
+    /// cualquiera (verificadores, titulares, suscriptores). Si la dirección
+    /// tiene perfil de usuario, además debe estar activo.
+    fn autenticar(env: &Env, direccion: &Address) {
+        direccion.require_auth();
+        if let Some(usuario) = Self::obtener_usuario(env.clone(), direccion.clone()) {
+            if !usuario.activo {
+                panic!("Usuario inactivo");
+            }
+        }
+    }
+
+    fn usuario_existente(env: &Env, direccion: &Address) -> Usuario {
+        match Self::obtener_usuario(env.clone(), direccion.clone()) {
+            Some(usuario) => usuario,
+            None => panic!("Usuario no encontrado"),
+        }
+    }
+
+    fn guardar_usuario(env: &Env, direccion: &Address, usuario: Usuario) {
+        let mut usuarios: Map<Address, Usuario> = env
+            .storage()
+            .persistent()
+            .get(&USUARIOS_KEY)
+            .unwrap_or(Map::new(env));
+        usuarios.set(direccion.clone(), usuario);
+        env.storage().persistent().set(&USUARIOS_KEY, &usuarios);
+    }
+
+    fn anotar_usuario(env: &Env, direccion: &Address, accion: AccionUsuario) {
+        let mut cambios = Self::obtener_bitacora_usuario(env.clone(), direccion.clone());
+        cambios.push_back(CambioUsuario {
+            accion,
+            fecha: env.ledger().timestamp(),
+        });
+        let mut bitacora: Map<Address, Vec<CambioUsuario>> = env
+            .storage()
+            .persistent()
+            .get(&BITACORA_KEY)
+            .unwrap_or(Map::new(env));
+        bitacora.set(direccion.clone(), cambios);
+        env.storage().persistent().set(&BITACORA_KEY, &bitacora);
+    }
+
+    /// Cobra a `pagador` la tarifa que `tarifa` elige, si hay tarifas
+    /// configuradas y es mayor que 0.
+    fn cobrar(env: &Env, pagador: &Address, tarifa: impl Fn(&Tarifas) -> i128) -> bool {
+        let Some(tarifas) = Self::obtener_tarifas(env.clone()) else {
+            return false;
+        };
+        let monto = tarifa(&tarifas);
+        if monto > 0 {
+            token::Client::new(env, &tarifas.token).transfer(pagador, &env.current_contract_address(), &monto);
+        }
+        monto > 0
+    }
+
+    /// Renueva la vida de la instancia y de las entradas persistentes dadas
+    /// que existan; la paga la tarifa de la operación que las toca.
+    fn extender_ttl(env: &Env, claves: &[soroban_sdk::Symbol]) {
+        env.storage().instance().extend_ttl(TTL_UMBRAL, TTL_EXTENSION);
+        for clave in claves {
+            if env.storage().persistent().has(clave) {
+                env.storage().persistent().extend_ttl(clave, TTL_UMBRAL, TTL_EXTENSION);
+            }
+        }
+    }
+
+    /// Acuña o quema el token del documento para que exista sólo si tiene
+    /// titular y está vigente (ver `ESTADOS_VIGENTES`). Emite `mint`/`burn`.
+    fn sincronizar_token(env: &Env, id_documento: i32) {
+        let titulares: Map<i32, Address> = env
+            .storage()
+            .persistent()
+            .get(&TITULARES_KEY)
+            .unwrap_or(Map::new(env));
+        let Some(titular) = titulares.get(id_documento) else {
+            return;
+        };
+        let activo = match Self::obtener_documento(env.clone(), id_documento) {
+            Some((_, estado, _)) => Self::estado_vigente(env, &estado),
+            None => false,
+        };
+
+        let mut tokens = Self::certificados_de(env.clone(), titular.clone());
+        let posicion = tokens.first_index_of(id_documento);
+        match (activo, posicion) {
+            (true, None) => {
+                tokens.push_back(id_documento);
+                env.events().publish((symbol_short!("mint"), titular.clone()), id_documento);
+            }
+            (false, Some(i)) => {
+                tokens.remove(i);
+                env.events().publish((symbol_short!("burn"), titular.clone()), id_documento);
+            }
+            _ => return,
+        }
+        let mut posesion: Map<Address, Vec<i32>> = env
+            .storage()
+            .persistent()
+            .get(&POSESION_KEY)
+            .unwrap_or(Map::new(env));
+        posesion.set(titular, tokens);
+        env.storage().persistent().set(&POSESION_KEY, &posesion);
+    }
+
+    /// Consume un crédito de `cuenta`. Devuelve `false` si no tenía.
+    fn debitar_credito(env: &Env, cuenta: &Address) -> bool {
+        let saldo = Self::saldo_creditos(env.clone(), cuenta.clone());
+        if saldo == 0 {
+            return false;
+        }
+        Self::fijar_creditos(env, cuenta, saldo - 1);
+        true
+    }
+
+    fn abonar_creditos(env: &Env, cuenta: &Address, creditos: u32) {
+        let saldo = Self::saldo_creditos(env.clone(), cuenta.clone());
+        Self::fijar_creditos(env, cuenta, saldo + creditos);
+    }
+
+    fn fijar_creditos(env: &Env, cuenta: &Address, saldo: u32) {
+        let mut creditos: Map<Address, u32> = env
+            .storage()
+            .persistent()
+            .get(&CREDITOS_KEY)
+            .unwrap_or(Map::new(env));
+        creditos.set(cuenta.clone(), saldo);
+        env.storage().persistent().set(&CREDITOS_KEY, &creditos);
+    }
+
+    fn admin(env: &Env) -> Address {
+        env.storage().persistent().get(&ADMIN_KEY).unwrap()
+    }
+
+    /// Da de alta un documento ya validado. Quien llama ya comprobó las
+    /// firmas.
+    fn alta_documento(env: &Env, id_documento: i32, titulo: String, estado: String, fecha_emision_academica: u64) {
+        // Obtenemos el mapa de documentos del almacenamiento persistente.
+        // Si no existe, crea un mapa vacío.
+        let mut documentos: Map<i32, (String, String, u64)> = env
+            .storage()
+            .persistent()
+            .get(&DOCUMENTS_KEY)
+            .unwrap_or(Map::new(env)); // Crea un mapa vacío si no hay nada
+
+        // Verificamos si el documento ya existe para evitar duplicados
+        if documentos.contains_key(id_documento) {
+            panic!("Documento con el mismo ID ya existe"); // Error si el documento ya está registrado
+        }
+
+        // Actualizamos los contadores de los informes
+        let mut estadisticas = Self::estadisticas(env);
+        estadisticas.total_documentos += 1;
+        Self::sumar(&mut estadisticas.por_estado, estado.clone(), 1);
+        Self::sumar(&mut estadisticas.por_mes, Self::mes(fecha_emision_academica), 1);
+        env.storage().persistent().set(&ESTADIST_KEY, &estadisticas);
+
+        // Añadimos el nuevo documento con sus detalles (titulo, estado y fecha)
+        documentos.set(id_documento, (titulo, estado, fecha_emision_academica));
+        // Guardamos el mapa actualizado en el almacenamiento persistente
+        env.storage().persistent().set(&DOCUMENTS_KEY, &documentos);
+
+        // La fecha de registro la fija el ledger, no quien llama
+        let mut registros: Map<i32, u64> = env
+            .storage()
+            .persistent()
+            .get(&REGISTRO_KEY)
+            .unwrap_or(Map::new(env));
+        registros.set(id_documento, env.ledger().timestamp());
+        env.storage().persistent().set(&REGISTRO_KEY, &registros);
+    }
+
+    /// Cambia el estado de un documento y actualiza su token y las
+    /// notificaciones. Quien llama ya comprobó las firmas.
+    fn cambiar_estado(env: &Env, id_documento: i32, nuevo_estado: String) {
+        let mut documentos: Map<i32, (soroban_sdk::String, soroban_sdk::String, u64)> = env
+            .storage()
+            .persistent()
+            .get(&DOCUMENTS_KEY)
+            .unwrap_or(Map::new(env));
+
+        if let Some((titulo, estado_anterior, fecha)) = documentos.get(id_documento) {
+            let mut estadisticas = Self::estadisticas(env);
+            Self::sumar(&mut estadisticas.por_estado, estado_anterior, -1);
+            Self::sumar(&mut estadisticas.por_estado, nuevo_estado.clone(), 1);
+            env.storage().persistent().set(&ESTADIST_KEY, &estadisticas);
+
+            documentos.set(id_documento, (titulo.clone(), nuevo_estado.clone(), fecha));
+            env.storage().persistent().set(&DOCUMENTS_KEY, &documentos);
+
+            Self::sincronizar_token(env, id_documento);
+            Self::notificar(env, id_documento, TipoEvento::CambioEstado, &nuevo_estado);
+            if nuevo_estado == String::from_str(env, ESTADO_REVOCADO) {
+                Self::notificar(env, id_documento, TipoEvento::Revocacion, &nuevo_estado);
+            }
+        } else {
+            panic!("Documento no encontrado");
+        }
+    }
+
+    /// Crea la nueva versión de un documento enmendado. Quien llama ya
+    /// comprobó las firmas.
+    fn aplicar_enmienda(env: &Env, id_documento: i32, cambios: Cambios, motivo: String) -> Version {
+        let mut documentos: Map<i32, (String, String, u64)> = env
+            .storage()
+            .persistent()
+            .get(&DOCUMENTS_KEY)
+            .unwrap_or(Map::new(env));
+
+        let (titulo_actual, estado, fecha_actual) = match documentos.get(id_documento) {
+            Some(documento) => documento,
+            None => panic!("Documento no encontrado"),
+        };
+
+        let titulo = cambios.titulo.unwrap_or(titulo_actual.clone());
+        let fecha = cambios.fecha_emision_academica.unwrap_or(fecha_actual);
+        Self::validar_documento(env, &titulo, &estado, fecha);
+        if titulo == titulo_actual && fecha == fecha_actual {
+            panic!("Enmienda sin cambios");
+        }
+
+        let mut versiones = Self::obtener_versiones(env.clone(), id_documento);
+        let ultima = versiones.len() - 1;
+        let mut anterior = versiones.get(ultima).unwrap();
+        anterior.reemplazada = true;
+        versiones.set(ultima, anterior.clone());
+        let version = Version {
+            numero: anterior.numero + 1,
+            anterior: Some(anterior.numero),
+            titulo: titulo.clone(),
+            fecha_emision_academica: fecha,
+            motivo,
+            registrada: env.ledger().timestamp(),
+            reemplazada: false,
+        };
+        versiones.push_back(version.clone());
+
+        let mut todas: Map<i32, Vec<Version>> = env
+            .storage()
+            .persistent()
+            .get(&VERSIONES_KEY)
+            .unwrap_or(Map::new(env));
+        todas.set(id_documento, versiones);
+        env.storage().persistent().set(&VERSIONES_KEY, &todas);
+
+        documentos.set(id_documento, (titulo, estado, fecha));
+        env.storage().persistent().set(&DOCUMENTS_KEY, &documentos);
+
+        if fecha != fecha_actual {
+            let mut estadisticas = Self::estadisticas(env);
+            Self::sumar(&mut estadisticas.por_mes, Self::mes(fecha_actual), -1);
+            Self::sumar(&mut estadisticas.por_mes, Self::mes(fecha), 1);
+            env.storage().persistent().set(&ESTADIST_KEY, &estadisticas);
+        }
+        version
+    }
+
+    /// `true` si `estado` es uno de `ESTADOS_VIGENTES`.
+    fn estado_vigente(env: &Env, estado: &String) -> bool {
+        ESTADOS_VIGENTES
+            .iter()
+            .any(|vigente| *estado == String::from_str(env, vigente))
+    }
+
+    /// Datos de emisión de un documento, si los tiene.
+    fn emision(env: &Env, id_documento: i32) -> Option<Emision> {
+        let emisiones: Map<i32, Emision> = env
+            .storage()
+            .persistent()
+            .get(&EMISION_KEY)
+            .unwrap_or(Map::new(env));
+        emisiones.get(id_documento)
+    }
+
+    /// Exige la firma de `responsable` para modificar un documento: debe
+    /// tener un perfil activo y, si el documento ya se emitió, responder de
+    /// esa emisión.
+    fn autorizar_responsable(env: &Env, id_documento: i32, responsable: &Address) {
+        if let Some(emision) = Self::emision(env, id_documento) {
+            if !Self::responde_de_emision(env, &emision, responsable) {
+                panic!("Sólo el emisor, su institución o el administrador pueden modificar un documento emitido");
+            }
+        }
+        Self::autorizar(env, responsable);
+    }
+
+    /// Cierra la clave vigente de una institución y abre `nueva` desde ahora.
+    /// La firma la pone la clave actual o, si `por_administrador`, el
+    /// administrador.
+    fn sustituir_clave(env: &Env, id_institucion: i32, nueva: Address, por_administrador: bool) {
+        let mut instituciones: Map<i32, Institucion> = env
+            .storage()
+            .persistent()
+            .get(&INSTITUC_KEY)
+            .unwrap_or(Map::new(env));
+
+        let mut institucion = match instituciones.get(id_institucion) {
+            Some(institucion) => institucion,
+            None => panic!("Institución no encontrada"),
+        };
+        let ultima = institucion.claves.len() - 1;
+        let mut actual = institucion.claves.get(ultima).unwrap();
+        if por_administrador {
+            Self::autorizar(env, &Self::admin(env));
+        } else {
+            Self::autorizar(env, &actual.direccion);
+        }
+        Self::autorizar(env, &nueva);
+
+        let ahora = env.ledger().timestamp();
+        actual.hasta = Some(ahora);
+        institucion.claves.set(ultima, actual);
+        institucion.claves.push_back(ClaveFirma {
+            direccion: nueva,
+            desde: ahora,
+            hasta: None,
+        });
+        instituciones.set(id_institucion, institucion);
+        env.storage().persistent().set(&INSTITUC_KEY, &instituciones);
+    }
+
+    /// `true` si `direccion` responde de un documento emitido: su emisor, la
+    /// clave vigente de su institución o el administrador.
+    fn responde_de_emision(env: &Env, emision: &Emision, direccion: &Address) -> bool {
+        if emision.emisor == *direccion || Self::admin(env) == *direccion {
+            return true;
+        }
+        match emision.id_institucion {
+            Some(id_institucion) => Self::clave_vigente(env, id_institucion).as_ref() == Some(direccion),
+            None => false,
+        }
+    }
+
+    /// Clave de firma vigente de una institución, si la tiene.
+    fn clave_vigente(env: &Env, id_institucion: i32) -> Option<Address> {
+        let institucion = Self::obtener_institucion(env.clone(), id_institucion)?;
+        let ultima = institucion.claves.last()?;
+        match ultima.hasta {
+            None => Some(ultima.direccion),
+            Some(_) => None,
+        }
+    }
+
+    /// Tipo de un documento registrado con `registrar_documento_tipado`.
+    fn tipo_de(env: &Env, id_documento: i32) -> Option<TipoDocumento> {
+        let tipado = Self::obtener_documento_tipado(env.clone(), id_documento)?;
+        Self::obtener_tipo(env.clone(), tipado.id_tipo)
+    }
+
+    fn titular_privado(env: &Env, id_documento: i32) -> Address {
+        let privados: Map<i32, CamposPrivados> = env
+            .storage()
+            .persistent()
+            .get(&PRIVADOS_KEY)
+            .unwrap_or(Map::new(env));
+
+        match privados.get(id_documento) {
+            Some(privado) => privado.titular,
+            None => panic!("Campos privados no encontrados"),
+        }
+    }
+
+    fn estadisticas(env: &Env) -> Estadisticas {
+        env.storage()
+            .persistent()
+            .get(&ESTADIST_KEY)
+            .unwrap_or(Estadisticas {
+                total_documentos: 0,
+                por_estado: Map::new(env),
+                por_mes: Map::new(env),
+                verificaciones_por_mes: Map::new(env),
+                por_institucion: Map::new(env),
+            })
+    }
+
+    fn sumar<K>(contadores: &mut Map<K, u32>, clave: K, delta: i32)
+    where
+        K: soroban_sdk::IntoVal<Env, soroban_sdk::Val> + soroban_sdk::TryFromVal<Env, soroban_sdk::Val> + Clone,
+    {
+        let actual = contadores.get(clave.clone()).unwrap_or(0);
+        contadores.set(clave, actual.saturating_add_signed(delta));
+    }
+
+    /// Mes (`AAAAMM`) de un timestamp Unix, en UTC.
+    fn mes(timestamp: u64) -> u32 {
+        // Algoritmo "civil from days" de Howard Hinnant
+        let dias = (timestamp / 86_400) as i64 + 719_468;
+        let era = dias / 146_097;
+        let dia_era = dias - era * 146_097;
+        let anio_era = (dia_era - dia_era / 1_460 + dia_era / 36_524 - dia_era / 146_096) / 365;
+        let dia_anio = dia_era - (365 * anio_era + anio_era / 4 - anio_era / 100);
+        let mp = (5 * dia_anio + 2) / 153;
+        let mes = if mp < 10 { mp + 3 } else { mp - 9 };
+        let anio = anio_era + era * 400 + if mes <= 2 { 1 } else { 0 };
+        (anio * 100 + mes) as u32
+    }
+
+    /// Hash encadenado sobre los documentos en el orden recibido (ver `Respaldo`).
+    fn digest_registro(
+        env: &Env,
+        documentos: impl Iterator<Item = (i32, (String, String, u64))>,
+    ) -> BytesN<32> {
+        let mut acumulado = BytesN::from_array(env, &[0; 32]);
+        for (id, (titulo, estado, fecha)) in documentos {
+            let hoja = env
+                .crypto()
+                .sha256(&(id, titulo, estado, fecha).to_xdr(env));
+            let mut datos = Bytes::from(acumulado);
+            datos.append(&Bytes::from(hoja.to_bytes()));
+            acumulado = env.crypto().sha256(&datos).to_bytes();
+        }
+        acumulado
+    }
 }
 
 #[cfg(test)]
@@ -1,12 +1,19 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{Env, String};
+use soroban_sdk::{
+    map, symbol_short,
+    testutils::{storage::Persistent as _, Address as _, AuthorizedFunction, Events as _, Ledger as _, MockAuth, MockAuthInvoke},
+    token, vec, Address, Bytes, BytesN, Env, IntoVal, String,
+};
 
 #[test]
 fn test_registrar_documento() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
 
     let id_documento = 1;
     let titulo = String::from_str(&env, "Certificado de Estudios");
@@ -15,7 +22,7 @@ fn test_registrar_documento() {
 
     // Registrar documento usando el contexto del contrato
     env.as_contract(&contract_id, || {
-        DocumentosContract::registrar_documento(env.clone(), id_documento, titulo.clone(), estado.clone(), fecha);
+        DocumentosContract::registrar_documento(env.clone(), id_documento, registrador.clone(), titulo.clone(), estado.clone(), fecha);
     });
 
     // Verificar que se registró correctamente
@@ -29,13 +36,22 @@ fn test_registrar_documento() {
     assert_eq!(titulo_obtenido, titulo);
     assert_eq!(estado_obtenido, estado);
     assert_eq!(fecha_obtenida, fecha);
+
+    // La fecha académica es la del documento; la de registro, la del ledger
+    let registrado = env.as_contract(&contract_id, || {
+        DocumentosContract::obtener_fecha_registro(env.clone(), id_documento)
+    });
+    assert_eq!(registrado, Some(1_700_000_000));
 }
 
 #[test]
 #[should_panic(expected = "Documento con el mismo ID ya existe")]
 fn test_registrar_documento_duplicado() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
 
     let id_documento = 1;
     let titulo = String::from_str(&env, "Certificado de Estudios");
@@ -44,19 +60,20 @@ fn test_registrar_documento_duplicado() {
 
     // Registrar documento por primera vez
     env.as_contract(&contract_id, || {
-        DocumentosContract::registrar_documento(env.clone(), id_documento, titulo.clone(), estado.clone(), fecha);
+        DocumentosContract::registrar_documento(env.clone(), id_documento, registrador.clone(), titulo.clone(), estado.clone(), fecha);
     });
     
     // Intentar registrar el mismo ID nuevamente (debe fallar)
     env.as_contract(&contract_id, || {
-        DocumentosContract::registrar_documento(env.clone(), id_documento, titulo, estado, fecha);
+        DocumentosContract::registrar_documento(env.clone(), id_documento, registrador.clone(), titulo, estado, fecha);
     });
 }
 
 #[test]
 fn test_obtener_documento_inexistente() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let resultado = env.as_contract(&contract_id, || {
         DocumentosContract::obtener_documento(env.clone(), 999)
@@ -68,15 +85,16 @@ fn test_obtener_documento_inexistente() {
 #[test]
 fn test_consulta_historial() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let id_historial = 1;
-    let fecha = 1640995200_u64;
+    let fecha = 1_700_000_000_u64; // la del ledger
     let resultado = String::from_str(&env, "Documento validado correctamente");
 
     // Añadir entrada al historial
     env.as_contract(&contract_id, || {
-        DocumentosContract::consulta_historial(env.clone(), id_historial, fecha, resultado.clone());
+        DocumentosContract::consulta_historial(env.clone(), id_historial, resultado.clone());
     });
 
     // Verificar que se guardó correctamente
@@ -95,36 +113,38 @@ fn test_consulta_historial() {
 #[should_panic(expected = "Historial con el mismo ID ya existe")]
 fn test_consulta_historial_duplicado() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let id_historial = 1;
-    let fecha = 1640995200_u64;
     let resultado = String::from_str(&env, "Documento validado");
 
     // Añadir entrada por primera vez
     env.as_contract(&contract_id, || {
-        DocumentosContract::consulta_historial(env.clone(), id_historial, fecha, resultado.clone());
+        DocumentosContract::consulta_historial(env.clone(), id_historial, resultado.clone());
     });
     
     // Intentar añadir la misma ID nuevamente (debe fallar)
     env.as_contract(&contract_id, || {
-        DocumentosContract::consulta_historial(env.clone(), id_historial, fecha, resultado);
+        DocumentosContract::consulta_historial(env.clone(), id_historial, resultado);
     });
 }
 
 #[test]
 fn test_realizar_respaldo() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let id_respaldo = 1;
-    let fecha = 1640995200_u64;
+    let fecha = 1_700_000_000_u64; // la del ledger
     let ubicacion = String::from_str(&env, "/backup/documentos/cert_001.pdf");
     let autor = String::from_str(&env, "Sistema Automatizado");
 
     // Realizar respaldo
     env.as_contract(&contract_id, || {
-        DocumentosContract::realizar_respaldo(env.clone(), id_respaldo, fecha, ubicacion.clone(), autor.clone());
+        DocumentosContract::realizar_respaldo(env.clone(), id_respaldo, ubicacion.clone(), autor.clone());
     });
 
     // Verificar que se guardó correctamente
@@ -134,62 +154,192 @@ fn test_realizar_respaldo() {
     
     assert!(respaldo_obtenido.is_some());
     
-    let (fecha_obtenida, ubicacion_obtenida, autor_obtenido) = respaldo_obtenido.unwrap();
-    assert_eq!(fecha_obtenida, fecha);
-    assert_eq!(ubicacion_obtenida, ubicacion);
-    assert_eq!(autor_obtenido, autor);
+    let respaldo_obtenido = respaldo_obtenido.unwrap();
+    assert_eq!(respaldo_obtenido.fecha, fecha);
+    assert_eq!(respaldo_obtenido.ubicacion, ubicacion);
+    assert_eq!(respaldo_obtenido.autor, autor);
+    assert_eq!(respaldo_obtenido.total_documentos, 0);
 }
 
 #[test]
 #[should_panic(expected = "Respaldo con ese ID ya existe")]
 fn test_realizar_respaldo_duplicado() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let id_respaldo = 1;
-    let fecha = 1640995200_u64;
     let ubicacion = String::from_str(&env, "/backup/test.pdf");
     let autor = String::from_str(&env, "Admin");
 
     // Realizar respaldo por primera vez
     env.as_contract(&contract_id, || {
-        DocumentosContract::realizar_respaldo(env.clone(), id_respaldo, fecha, ubicacion.clone(), autor.clone());
+        DocumentosContract::realizar_respaldo(env.clone(), id_respaldo, ubicacion.clone(), autor.clone());
     });
     
     // Intentar realizar respaldo con el mismo ID (debe fallar)
     env.as_contract(&contract_id, || {
-        DocumentosContract::realizar_respaldo(env.clone(), id_respaldo, fecha, ubicacion, autor);
+        DocumentosContract::realizar_respaldo(env.clone(), id_respaldo, ubicacion, autor);
+    });
+}
+
+#[test]
+fn test_respaldo_digest() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let titulo = String::from_str(&env, "Certificado de Estudios");
+    let estado = String::from_str(&env, "Activo");
+    let fecha = 1640995200_u64;
+    let ubicacion = String::from_str(&env, "/backup/registro.jsonl");
+    let autor = String::from_str(&env, "Admin");
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 2, registrador.clone(), titulo.clone(), estado.clone(), fecha);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), titulo.clone(), estado.clone(), fecha);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::realizar_respaldo(env.clone(), 1, ubicacion.clone(), autor.clone());
+    });
+
+    // El digest es el que recalcula una exportación ordenada por ID
+    let exportacion = vec![
+        &env,
+        (1, (titulo.clone(), estado.clone(), fecha)),
+        (2, (titulo.clone(), estado.clone(), fecha)),
+    ];
+    let (primero, esperado) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::consultar_respaldo(env.clone(), 1).unwrap(),
+            DocumentosContract::digest_registro(&env, exportacion.iter()),
+        )
+    });
+    assert_eq!(primero.total_documentos, 2);
+    assert_eq!(primero.digest, esperado);
+
+    // Un cambio posterior en el registro no altera el respaldo ya tomado
+    env.as_contract(&contract_id, || {
+        DocumentosContract::actualizar_estado(env.clone(), 1, registrador.clone(), String::from_str(&env, "Revocado"));
+        DocumentosContract::realizar_respaldo(env.clone(), 2, ubicacion.clone(), autor.clone());
+    });
+    let (primero, segundo) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::consultar_respaldo(env.clone(), 1).unwrap(),
+            DocumentosContract::consultar_respaldo(env.clone(), 2).unwrap(),
+        )
     });
+    assert_eq!(primero.digest, esperado);
+    assert_ne!(primero.digest, segundo.digest);
 }
 
 #[test]
-fn test_verificar_documento() {
+fn test_registrar_verificacion() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
 
     let id_documento = 1;
-    let nuevo_resultado = String::from_str(&env, "Documento verificado exitosamente");
+    let empleador = Address::generate(&env);
+    let universidad = Address::generate(&env);
+    env.ledger().set_timestamp(1_700_000_000);
 
-    // Verificar documento (esto debe crear una entrada en el historial)
     env.as_contract(&contract_id, || {
-        DocumentosContract::verificar_documento(env.clone(), id_documento, nuevo_resultado.clone());
+        DocumentosContract::registrar_documento(env.clone(), id_documento, registrador.clone(), String::from_str(&env, "Diploma"), String::from_str(&env, "Activo"), 1640995200);
     });
 
-    // Obtener el historial y verificar que se actualizó
-    let historial = env.as_contract(&contract_id, || {
-        DocumentosContract::obtener_historial(env.clone(), id_documento)
+    // Cada verificación se añade al registro, sin sobrescribir la anterior
+    let resultado = env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), id_documento, empleador.clone(), None)
     });
-    
-    assert!(historial.is_some());
-    
-    let (_, resultado_obtenido) = historial.unwrap();
-    assert_eq!(resultado_obtenido, nuevo_resultado);
+    assert!(resultado.valido);
+    env.ledger().set_timestamp(1_700_000_100);
+    let resultado = env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 99, universidad.clone(), None)
+    });
+    assert_eq!(resultado.motivos, vec![&env, MotivoRechazo::NoEncontrado]);
+
+    let (verificaciones, inexistente) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::obtener_verificaciones(env.clone(), id_documento),
+            DocumentosContract::obtener_verificaciones(env.clone(), 99),
+        )
+    });
+    assert_eq!(
+        verificaciones,
+        vec![&env, Verificacion { verificador: empleador, fecha: 1_700_000_000, valido: true }]
+    );
+    assert_eq!(
+        inexistente,
+        vec![&env, Verificacion { verificador: universidad, fecha: 1_700_000_100, valido: false }]
+    );
+}
+
+#[test]
+fn test_registro_verificaciones_acotado() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    cliente.registrar_documento(&1, &registrador, &String::from_str(&env, "Diploma"), &String::from_str(&env, "Activo"), &1640995200);
+
+    let verificador = Address::generate(&env);
+    for segundo in 0..40 {
+        env.ledger().set_timestamp(1_700_000_000 + segundo);
+        cliente.registrar_verificacion(&1, &verificador, &None);
+    }
+    // Sólo se conservan las más recientes
+    let verificaciones = cliente.obtener_verificaciones(&1);
+    assert_eq!(verificaciones.len(), 32);
+    assert_eq!(verificaciones.get(0).unwrap().fecha, 1_700_000_008);
+    assert_eq!(verificaciones.last().unwrap().fecha, 1_700_000_039);
+    assert!(cliente.obtener_verificaciones(&2).is_empty());
+}
+
+#[test]
+fn test_estados_vigentes() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let titular = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    cliente.registrar_documento(&1, &registrador, &String::from_str(&env, "Diploma"), &String::from_str(&env, "Activo"), &1640995200);
+    cliente.registrar_emision(&1, &emisor, &None, &hash, &hash, &None);
+    cliente.vincular_titular(&1, &titular);
+
+    // La verificación y el token siguen la misma regla
+    for (estado, vigente) in [
+        ("Validado", true),
+        ("revocado", false),
+        ("Anulado", false),
+        ("Suspendido", false),
+        ("Activo", true),
+    ] {
+        cliente.actualizar_estado(&1, &emisor, &String::from_str(&env, estado));
+        let resultado = cliente.verificar_documento_publico(&1, &None);
+        assert_eq!(resultado.valido, vigente, "{estado}");
+        assert_eq!(cliente.balance(&titular), vigente as i128, "{estado}");
+        if !vigente {
+            assert_eq!(resultado.motivos, vec![&env, MotivoRechazo::NoVigente]);
+        }
+    }
 }
 
 #[test]
 fn test_actualizar_estado() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
 
     // Primero registrar un documento
     let id_documento = 1;
@@ -198,13 +348,13 @@ fn test_actualizar_estado() {
     let fecha = 1640995200_u64;
 
     env.as_contract(&contract_id, || {
-        DocumentosContract::registrar_documento(env.clone(), id_documento, titulo.clone(), estado_inicial, fecha);
+        DocumentosContract::registrar_documento(env.clone(), id_documento, registrador.clone(), titulo.clone(), estado_inicial, fecha);
     });
 
     // Actualizar el estado
     let nuevo_estado = String::from_str(&env, "Validado");
     env.as_contract(&contract_id, || {
-        DocumentosContract::actualizar_estado(env.clone(), id_documento, nuevo_estado.clone());
+        DocumentosContract::actualizar_estado(env.clone(), id_documento, registrador.clone(), nuevo_estado.clone());
     });
 
     // Verificar que el estado se actualizó
@@ -224,55 +374,145 @@ fn test_actualizar_estado() {
 #[should_panic(expected = "Documento no encontrado")]
 fn test_actualizar_estado_documento_inexistente() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
 
     let nuevo_estado = String::from_str(&env, "Validado");
     
     // Intentar actualizar un documento que no existe
     env.as_contract(&contract_id, || {
-        DocumentosContract::actualizar_estado(env.clone(), 999, nuevo_estado);
+        DocumentosContract::actualizar_estado(env.clone(), 999, registrador.clone(), nuevo_estado);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Sólo el emisor, su institución o el administrador pueden modificar un documento emitido")]
+fn test_actualizar_estado_documento_emitido() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Diploma"), String::from_str(&env, "Activo"), 1640995200);
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, hash.clone(), hash.clone(), None);
+    });
+    // Una vez emitido, quien lo registró ya no puede revocarlo
+    env.as_contract(&contract_id, || {
+        DocumentosContract::actualizar_estado(env.clone(), 1, registrador.clone(), String::from_str(&env, "Revocado"));
+    });
+}
+
+#[test]
+fn test_revocar_emitido_por_emisor_dado_de_baja() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let clave_antigua = alta_usuario(&env, &contract_id, "Emisor");
+    let clave_nueva = alta_usuario(&env, &contract_id, "Emisor");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let activo = String::from_str(&env, "Activo");
+    let revocado = String::from_str(&env, "Revocado");
+
+    cliente.registrar_institucion(&1, &String::from_str(&env, "Universidad de Prueba"), &clave_antigua);
+    for id in 1..=2 {
+        cliente.registrar_documento(&id, &registrador, &String::from_str(&env, "Diploma"), &activo, &1640995200);
+    }
+    cliente.registrar_emision(&1, &emisor, &None, &hash, &hash, &None);
+    cliente.registrar_emision(&2, &clave_antigua, &Some(1), &hash, &hash, &None);
+
+    // El emisor dado de baja ya no puede revocar, pero el administrador sí
+    cliente.desactivar_usuario(&emisor);
+    assert!(cliente.try_actualizar_estado(&1, &emisor, &revocado).is_err());
+    cliente.actualizar_estado(&1, &cliente.obtener_admin(), &revocado);
+    assert_eq!(cliente.obtener_documento(&1).unwrap().1, revocado);
+
+    // Tras rotar la clave, la nueva responde de lo que firmó la antigua
+    env.ledger().set_timestamp(1_700_000_100);
+    cliente.rotar_clave(&1, &clave_nueva);
+    cliente.actualizar_estado(&2, &clave_nueva, &revocado);
+    assert_eq!(cliente.obtener_documento(&2).unwrap().1, revocado);
+}
+
+/// Despliega el contrato con un administrador cualquiera.
+fn desplegar(env: &Env) -> Address {
+    env.register(DocumentosContract, (Address::generate(env),))
+}
+
+fn registro_con_admin(env: &Env) -> Address {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    desplegar(env)
+}
+
+/// Da de alta el perfil de una dirección nueva con el rol `rol`. Requiere
+/// `mock_all_auths`.
+fn alta_usuario(env: &Env, contract_id: &Address, rol: &str) -> Address {
+    let direccion = Address::generate(env);
+    env.as_contract(contract_id, || {
+        DocumentosContract::crear_usuario(env.clone(), direccion.clone(), None, None, String::from_str(env, rol));
     });
+    direccion
 }
 
 #[test]
 fn test_crear_usuario() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    let contract_id = registro_con_admin(&env);
 
-    let id_usuario = 1;
+    let usuario = Address::generate(&env);
     let nombre = String::from_str(&env, "Juan Pérez");
+    let hash_email = BytesN::from_array(&env, &[9; 32]);
     let rol = String::from_str(&env, "Administrador");
 
     // Crear usuario
     env.as_contract(&contract_id, || {
-        DocumentosContract::crear_usuario(env.clone(), id_usuario, nombre, rol);
+        DocumentosContract::crear_usuario(env.clone(), usuario.clone(), Some(nombre.clone()), Some(hash_email.clone()), rol.clone());
     });
 
-    // Verificar que se puede asignar un rol (indirectamente verifica que existe)
     let nuevo_rol = String::from_str(&env, "Super Admin");
     env.as_contract(&contract_id, || {
-        DocumentosContract::asignar_rol(env.clone(), id_usuario, nuevo_rol);
+        DocumentosContract::asignar_rol(env.clone(), usuario.clone(), nuevo_rol.clone(), None);
     });
+
+    let perfil = env.as_contract(&contract_id, || DocumentosContract::obtener_usuario(env.clone(), usuario.clone()));
+    assert_eq!(
+        perfil,
+        Some(Usuario {
+            nombre: Some(nombre),
+            hash_email: Some(hash_email.into()),
+            roles: vec![
+                &env,
+                ConcesionRol { rol, hasta: None },
+                ConcesionRol { rol: nuevo_rol, hasta: None },
+            ],
+            activo: true,
+        })
+    );
 }
 
 #[test]
 #[should_panic(expected = "Usuario ya existe")]
 fn test_crear_usuario_duplicado() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    let contract_id = registro_con_admin(&env);
 
-    let id_usuario = 1;
-    let nombre = String::from_str(&env, "Juan Pérez");
+    let usuario = Address::generate(&env);
     let rol = String::from_str(&env, "Admin");
 
     // Crear usuario por primera vez
     env.as_contract(&contract_id, || {
-        DocumentosContract::crear_usuario(env.clone(), id_usuario, nombre.clone(), rol.clone());
+        DocumentosContract::crear_usuario(env.clone(), usuario.clone(), None, None, rol.clone());
     });
     
     // Intentar crear el mismo usuario nuevamente
     env.as_contract(&contract_id, || {
-        DocumentosContract::crear_usuario(env.clone(), id_usuario, nombre, rol);
+        DocumentosContract::crear_usuario(env.clone(), usuario.clone(), None, None, rol);
     });
 }
 
@@ -280,27 +520,224 @@ fn test_crear_usuario_duplicado() {
 #[should_panic(expected = "Usuario no encontrado")]
 fn test_asignar_rol_usuario_inexistente() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    let contract_id = registro_con_admin(&env);
 
     let nuevo_rol = String::from_str(&env, "Admin");
     
     // Intentar asignar rol a usuario inexistente
     env.as_contract(&contract_id, || {
-        DocumentosContract::asignar_rol(env.clone(), 999, nuevo_rol);
+        DocumentosContract::asignar_rol(env.clone(), Address::generate(&env), nuevo_rol, None);
+    });
+}
+
+#[test]
+fn test_ciclo_de_vida_usuario() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+
+    let usuario = Address::generate(&env);
+    let registrador = String::from_str(&env, "Registrador");
+    env.as_contract(&contract_id, || {
+        DocumentosContract::crear_usuario(env.clone(), usuario.clone(), Some(String::from_str(&env, "Marta Gil")), None, registrador.clone());
+    });
+    let permitido = env.as_contract(&contract_id, || DocumentosContract::tiene_rol(env.clone(), usuario.clone(), registrador.clone()));
+    assert!(permitido);
+
+    // Un usuario desactivado no supera las comprobaciones de permisos
+    env.ledger().set_timestamp(1_700_000_100);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::desactivar_usuario(env.clone(), usuario.clone());
+    });
+    let permitido = env.as_contract(&contract_id, || DocumentosContract::tiene_rol(env.clone(), usuario.clone(), registrador.clone()));
+    assert!(!permitido);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::reactivar_usuario(env.clone(), usuario.clone());
+    });
+    let permitido = env.as_contract(&contract_id, || DocumentosContract::tiene_rol(env.clone(), usuario.clone(), registrador.clone()));
+    assert!(permitido);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::eliminar_usuario(env.clone(), usuario.clone());
+    });
+    let (perfil, bitacora) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::obtener_usuario(env.clone(), usuario.clone()),
+            DocumentosContract::obtener_bitacora_usuario(env.clone(), usuario.clone()),
+        )
+    });
+    assert_eq!(perfil, None);
+    let mut acciones = Vec::new(&env);
+    for cambio in bitacora.iter() {
+        acciones.push_back(cambio.accion);
+    }
+    assert_eq!(
+        acciones,
+        vec![&env, AccionUsuario::Creado, AccionUsuario::Desactivado, AccionUsuario::Reactivado, AccionUsuario::Eliminado]
+    );
+    assert_eq!(bitacora.get(1).unwrap().fecha, 1_700_000_100);
+}
+
+#[test]
+fn test_rol_temporal() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+
+    let temporal = Address::generate(&env);
+    let asistente = String::from_str(&env, "Asistente");
+    let emisor = String::from_str(&env, "Emisor");
+    let dos_semanas = 1_700_000_000 + 14 * 86_400;
+    env.as_contract(&contract_id, || {
+        DocumentosContract::crear_usuario(env.clone(), temporal.clone(), None, None, asistente.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::asignar_rol(env.clone(), temporal.clone(), emisor.clone(), Some(dos_semanas));
+    });
+
+    let (vigentes, puede_emitir) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::roles_vigentes(env.clone(), temporal.clone()),
+            DocumentosContract::tiene_rol(env.clone(), temporal.clone(), emisor.clone()),
+        )
+    });
+    assert_eq!(
+        vigentes,
+        vec![
+            &env,
+            ConcesionRol { rol: asistente.clone(), hasta: None },
+            ConcesionRol { rol: emisor.clone(), hasta: Some(dos_semanas) },
+        ]
+    );
+    assert!(puede_emitir);
+
+    // Pasado el plazo, la concesión deja de valer sin tocar el registro
+    env.ledger().set_timestamp(dos_semanas);
+    let (vigentes, puede_emitir) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::roles_vigentes(env.clone(), temporal.clone()),
+            DocumentosContract::tiene_rol(env.clone(), temporal.clone(), emisor.clone()),
+        )
+    });
+    assert_eq!(vigentes, vec![&env, ConcesionRol { rol: asistente.clone(), hasta: None }]);
+    assert!(!puede_emitir);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::revocar_rol(env.clone(), temporal.clone(), asistente.clone());
+    });
+    let vigentes = env.as_contract(&contract_id, || DocumentosContract::roles_vigentes(env.clone(), temporal.clone()));
+    assert!(vigentes.is_empty());
+}
+
+#[test]
+fn test_emision_requiere_rol_vigente() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let temporal = alta_usuario(&env, &contract_id, "Asistente");
+    let titular = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let titulo = String::from_str(&env, "Diploma");
+    let activo = String::from_str(&env, "Activo");
+    let dos_semanas = 1_700_000_000 + 14 * 86_400;
+    cliente.registrar_documento(&1, &registrador, &titulo, &activo, &1640995200);
+    cliente.registrar_documento(&2, &registrador, &titulo, &activo, &1640995200);
+
+    // Sin el rol no se emite
+    assert!(cliente.try_registrar_emision(&1, &temporal, &None, &hash, &hash, &None).is_err());
+
+    cliente.asignar_rol(&temporal, &String::from_str(&env, "Emisor"), &Some(dos_semanas));
+    cliente.registrar_emision(&1, &temporal, &None, &hash, &hash, &None);
+
+    // Pasado `hasta` ya no puede emitir ni vincular titulares
+    env.ledger().set_timestamp(dos_semanas);
+    assert!(cliente.try_registrar_emision(&2, &temporal, &None, &hash, &hash, &None).is_err());
+    assert!(cliente.try_vincular_titular(&1, &titular).is_err());
+    assert!(cliente.verificar_documento_publico(&2, &None).emisor.is_none());
+}
+
+#[test]
+#[should_panic(expected = "Usuario ya inactivo")]
+fn test_desactivar_usuario_inactivo() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let usuario = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::crear_usuario(env.clone(), usuario.clone(), None, None, String::from_str(&env, "Registrador"));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::desactivar_usuario(env.clone(), usuario.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::desactivar_usuario(env.clone(), usuario.clone());
+    });
+}
+
+#[test]
+#[should_panic(expected = "Usuario inactivo")]
+fn test_usuario_inactivo_no_firma() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[1; 32]);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::crear_usuario(env.clone(), emisor.clone(), None, None, String::from_str(&env, "Emisor"));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::desactivar_usuario(env.clone(), emisor.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Diploma"), String::from_str(&env, "Activo"), 1640995200);
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, hash.clone(), hash.clone(), None);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Usuario no encontrado")]
+fn test_usuario_eliminado_no_firma() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+
+    // Borrar el perfil de un usuario desactivado no le devuelve la firma
+    env.as_contract(&contract_id, || {
+        DocumentosContract::desactivar_usuario(env.clone(), emisor.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::eliminar_usuario(env.clone(), emisor.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Diploma"), String::from_str(&env, "Activo"), 1640995200);
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, hash.clone(), hash.clone(), None);
     });
 }
 
 #[test]
 fn test_configurar_notificacion() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
 
     let id_notificacion = 1;
-    let tipo = String::from_str(&env, "Email");
+    let suscriptor = Address::generate(&env);
+    let canal = String::from_str(&env, "smtp:titular@example.com");
 
     // Configurar notificación
     env.as_contract(&contract_id, || {
-        DocumentosContract::configurar_notificacion(env.clone(), id_notificacion, tipo.clone());
+        DocumentosContract::configurar_notificacion(
+            env.clone(),
+            id_notificacion,
+            suscriptor.clone(),
+            None,
+            TipoEvento::Revocacion,
+            canal.clone(),
+        );
     });
 
     // Verificar que se puede enviar (obtener) la notificación
@@ -309,13 +746,130 @@ fn test_configurar_notificacion() {
     });
     
     assert!(notificacion.is_some());
-    assert_eq!(notificacion.unwrap(), tipo);
+    
+    let notificacion = notificacion.unwrap();
+    assert_eq!(notificacion.suscriptor, suscriptor);
+    assert_eq!(notificacion.evento, TipoEvento::Revocacion);
+    assert_eq!(notificacion.canal, canal);
+    assert_eq!(env.events().all().len(), 1);
+}
+
+#[test]
+fn test_notificaciones_por_evento() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let titular = Address::generate(&env);
+    let verificador = Address::generate(&env);
+    let titulo = String::from_str(&env, "Diploma");
+    let revocado = String::from_str(&env, "Revocado");
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), titulo.clone(), String::from_str(&env, "Activo"), 1640995200);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 2, registrador.clone(), titulo.clone(), String::from_str(&env, "Activo"), 1640995200);
+    });
+    env.as_contract(&contract_id, || {
+        // El titular quiere saber si se revoca el documento 1
+        DocumentosContract::configurar_notificacion(
+            env.clone(),
+            1,
+            titular.clone(),
+            Some(1),
+            TipoEvento::Revocacion,
+            String::from_str(&env, "smtp:titular@example.com"),
+        );
+        // El verificador sigue cualquier cambio de estado de cualquier documento
+        DocumentosContract::configurar_notificacion(
+            env.clone(),
+            2,
+            verificador.clone(),
+            None,
+            TipoEvento::CambioEstado,
+            String::from_str(&env, "webhook:http://127.0.0.1:8080/avisos"),
+        );
+    });
+
+    // Un cambio de estado del documento 2 sólo avisa al verificador
+    env.as_contract(&contract_id, || {
+        DocumentosContract::actualizar_estado(env.clone(), 2, registrador.clone(), String::from_str(&env, "Validado"));
+    });
+    let eventos = env.events().all();
+    assert_eq!(eventos.len(), 1);
+    let (contrato, topicos, datos) = eventos.get(0).unwrap();
+    assert_eq!(contrato, contract_id);
+    assert_eq!(topicos, (symbol_short!("notif"), verificador.clone(), 2).into_val(&env));
+    let datos: Notificacion = datos.into_val(&env);
+    assert_eq!(datos.id_notificacion, 2);
+    assert_eq!(datos.evento, TipoEvento::CambioEstado);
+
+    // La revocación del documento 1 avisa a ambos
+    env.as_contract(&contract_id, || {
+        DocumentosContract::actualizar_estado(env.clone(), 1, registrador.clone(), revocado.clone());
+    });
+    let eventos = env.events().all();
+    assert_eq!(eventos.len(), 2);
+    let (_, topicos, datos) = eventos.get(1).unwrap();
+    assert_eq!(topicos, (symbol_short!("notif"), titular.clone(), 1).into_val(&env));
+    let datos: Notificacion = datos.into_val(&env);
+    assert_eq!(datos.evento, TipoEvento::Revocacion);
+    assert_eq!(datos.estado, revocado);
+}
+
+#[test]
+#[should_panic(expected = "Documento no encontrado")]
+fn test_configurar_notificacion_documento_inexistente() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::configurar_notificacion(
+            env.clone(),
+            1,
+            Address::generate(&env),
+            Some(999),
+            TipoEvento::CambioEstado,
+            String::from_str(&env, "archivo:/tmp/avisos.jsonl"),
+        );
+    });
+}
+
+#[test]
+fn test_cancelar_notificacion() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::configurar_notificacion(
+            env.clone(),
+            1,
+            Address::generate(&env),
+            None,
+            TipoEvento::Verificacion,
+            String::from_str(&env, "archivo:/tmp/avisos.jsonl"),
+        );
+        DocumentosContract::cancelar_notificacion(env.clone(), 1);
+    });
+
+    let notificacion = env.as_contract(&contract_id, || {
+        DocumentosContract::enviar_notificacion(env.clone(), 1)
+    });
+    assert!(notificacion.is_none());
 }
 
 #[test]
 fn test_enviar_notificacion_inexistente() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let notificacion = env.as_contract(&contract_id, || {
         DocumentosContract::enviar_notificacion(env.clone(), 999)
@@ -324,10 +878,76 @@ fn test_enviar_notificacion_inexistente() {
     assert!(notificacion.is_none());
 }
 
+#[test]
+fn test_limites_suscripciones() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    for id in 1..=4 {
+        cliente.registrar_documento(&id, &registrador, &String::from_str(&env, "Diploma"), &String::from_str(&env, "Activo"), &1640995200);
+    }
+    let canal = String::from_str(&env, "smtp:titular@example.com");
+
+    assert_eq!(
+        cliente.try_configurar_notificacion(&1, &registrador, &Some(1), &TipoEvento::Revocacion, &String::from_str(&env, "")),
+        Err(Ok(ErrorValidacion::CanalVacio.into()))
+    );
+    assert_eq!(
+        cliente.try_configurar_notificacion(&1, &registrador, &Some(1), &TipoEvento::Revocacion, &String::from_str(&env, &"x".repeat(257))),
+        Err(Ok(ErrorValidacion::CanalDemasiadoLargo.into()))
+    );
+    for canal in ["ftp://ejemplo.org", "smtp:"] {
+        assert_eq!(
+            cliente.try_configurar_notificacion(&1, &registrador, &Some(1), &TipoEvento::Revocacion, &String::from_str(&env, canal)),
+            Err(Ok(ErrorValidacion::CanalNoValido.into()))
+        );
+    }
+
+    // Cada documento admite un número acotado de suscripciones
+    for id in 0..8 {
+        cliente.configurar_notificacion(&id, &Address::generate(&env), &Some(1), &TipoEvento::Revocacion, &canal);
+    }
+    assert!(cliente.try_configurar_notificacion(&8, &Address::generate(&env), &Some(1), &TipoEvento::Revocacion, &canal).is_err());
+    // y cada suscriptor también
+    let suscriptor = Address::generate(&env);
+    for id in 0..16 {
+        cliente.configurar_notificacion(&(100 + id), &suscriptor, &Some(2 + id / 8), &TipoEvento::CambioEstado, &canal);
+    }
+    assert!(cliente.try_configurar_notificacion(&116, &suscriptor, &Some(4), &TipoEvento::CambioEstado, &canal).is_err());
+    cliente.cancelar_notificacion(&100);
+    cliente.configurar_notificacion(&116, &suscriptor, &Some(4), &TipoEvento::CambioEstado, &canal);
+
+    // La revocación sólo recorre las suscripciones del documento
+    cliente.actualizar_estado(&1, &registrador, &String::from_str(&env, "Revocado"));
+    assert_eq!(env.events().all().len(), 8);
+}
+
+#[test]
+fn test_suscripciones_requieren_firma() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let admin = cliente.obtener_admin();
+    let suscriptor = Address::generate(&env);
+    let canal = String::from_str(&env, "webhook:http://127.0.0.1:8080/avisos");
+    cliente.configurar_notificacion(&1, &suscriptor, &None, &TipoEvento::CambioEstado, &canal);
+
+    // Seguir todos los documentos necesita también al administrador
+    assert!(env.auths().iter().any(|(direccion, _)| *direccion == admin));
+
+    // Sin firmas no se envían avisos de prueba ni se cancela
+    env.mock_auths(&[]);
+    assert!(cliente.try_enviar_notificacion(&1).is_err());
+    assert!(cliente.try_cancelar_notificacion(&1).is_err());
+    assert!(cliente.try_configurar_notificacion(&2, &suscriptor, &None, &TipoEvento::CambioEstado, &canal).is_err());
+}
+
 #[test]
 fn test_autenticar_usuario() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let id_sesion = 1;
     let credenciales = String::from_str(&env, "token_auth_12345");
@@ -348,7 +968,8 @@ fn test_autenticar_usuario() {
 #[should_panic(expected = "Sesión no encontrada")]
 fn test_asignar_permisos_sesion_inexistente() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let permisos = String::from_str(&env, "read,write");
     
@@ -361,14 +982,16 @@ fn test_asignar_permisos_sesion_inexistente() {
 #[test]
 fn test_generar_informe() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let id_informe = 1;
-    let formato = String::from_str(&env, "PDF");
+    let rango = Rango { desde: 0, hasta: u64::MAX / 2 };
 
     // Generar informe
-    env.as_contract(&contract_id, || {
-        DocumentosContract::generar_informe(env.clone(), id_informe, formato.clone());
+    let generado = env.as_contract(&contract_id, || {
+        DocumentosContract::generar_informe(env.clone(), id_informe, TipoInforme::General, rango.clone())
     });
 
     // Exportar informe y verificar
@@ -378,16 +1001,123 @@ fn test_generar_informe() {
     
     assert!(informe.is_some());
     
-    let (fecha_generacion, formato_obtenido) = informe.unwrap();
-    assert_eq!(formato_obtenido, formato);
+    let informe = informe.unwrap();
+    assert_eq!(informe, generado);
+    assert_eq!(informe.tipo, TipoInforme::General);
+    assert_eq!(informe.rango, rango);
+    assert_eq!(informe.total_documentos, 0);
     // En el entorno de pruebas, la fecha puede ser 0, lo cual es válido
-    assert!(fecha_generacion >= 0); // Verificar que tiene una fecha válida (incluye 0)
+    assert_eq!(informe.fecha_generacion, env.ledger().timestamp());
+}
+
+#[test]
+fn test_generar_informe_estadisticas() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let verificador = Address::generate(&env);
+    let firmante = alta_usuario(&env, &contract_id, "Emisor");
+
+    let enero_2022 = 1640995200_u64;
+    let marzo_2022 = 1646092800_u64;
+    let titulo = String::from_str(&env, "Diploma");
+    let activo = String::from_str(&env, "Activo");
+    let revocado = String::from_str(&env, "Revocado");
+
+    env.ledger().set_timestamp(marzo_2022 + 3600);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), titulo.clone(), activo.clone(), enero_2022);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 2, registrador.clone(), titulo.clone(), activo.clone(), enero_2022 + 86400);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 3, registrador.clone(), titulo.clone(), activo.clone(), marzo_2022);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::actualizar_estado(env.clone(), 2, registrador.clone(), revocado.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_institucion(env.clone(), 7, String::from_str(&env, "Universidad"), firmante.clone());
+    });
+    for id in [1, 3] {
+        env.as_contract(&contract_id, || {
+            DocumentosContract::registrar_emision(
+                env.clone(),
+                id,
+                firmante.clone(),
+                Some(7),
+                BytesN::from_array(&env, &[1; 32]),
+                BytesN::from_array(&env, &[2; 32]),
+                None,
+            );
+        });
+    }
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 1, verificador.clone(), None);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 3, verificador.clone(), None);
+    });
+
+    let general = env.as_contract(&contract_id, || {
+        DocumentosContract::generar_informe(
+            env.clone(),
+            1,
+            TipoInforme::General,
+            Rango { desde: enero_2022, hasta: marzo_2022 + 86400 },
+        )
+    });
+    assert_eq!(general.total_documentos, 3);
+    assert_eq!(general.por_estado, map![&env, (activo.clone(), 2), (revocado.clone(), 1)]);
+    assert_eq!(general.por_mes, map![&env, (202201, 2), (202203, 1)]);
+    assert_eq!(general.verificaciones, 2);
+    assert_eq!(general.por_institucion, map![&env, (7, 2)]);
+
+    // Un rango que sólo cubre enero deja fuera marzo y sus verificaciones
+    let por_mes = env.as_contract(&contract_id, || {
+        DocumentosContract::generar_informe(
+            env.clone(),
+            2,
+            TipoInforme::PorMes,
+            Rango { desde: enero_2022, hasta: enero_2022 + 86400 * 30 },
+        )
+    });
+    assert_eq!(por_mes.por_mes, map![&env, (202201, 2)]);
+    assert!(por_mes.por_estado.is_empty());
+    assert!(por_mes.por_institucion.is_empty());
+    assert_eq!(por_mes.verificaciones, 0);
+
+    // Los contadores son mensuales: un rango de un día cuenta todo su mes
+    let un_dia = env.as_contract(&contract_id, || {
+        DocumentosContract::generar_informe(
+            env.clone(),
+            3,
+            TipoInforme::PorMes,
+            Rango { desde: enero_2022 + 86400 * 20, hasta: enero_2022 + 86400 * 21 },
+        )
+    });
+    assert_eq!(un_dia.por_mes, map![&env, (202201, 2)]);
+}
+
+#[test]
+#[should_panic(expected = "Rango de fechas inválido")]
+fn test_generar_informe_rango_invalido() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::generar_informe(env.clone(), 1, TipoInforme::General, Rango { desde: 10, hasta: 5 });
+    });
 }
 
 #[test]
 fn test_exportar_informe_inexistente() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
 
     let informe = env.as_contract(&contract_id, || {
         DocumentosContract::exportar_informe(env.clone(), 999)
@@ -399,14 +1129,16 @@ fn test_exportar_informe_inexistente() {
 #[test]
 fn test_exportar_datos() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    let contract_id = registro_con_admin(&env);
 
     let id_exportacion = 1;
     let formato = String::from_str(&env, "JSON");
+    let hash = BytesN::from_array(&env, &[7; 32]);
+    let filas = 12;
 
     // Exportar datos
     env.as_contract(&contract_id, || {
-        DocumentosContract::exportar_datos(env.clone(), id_exportacion, formato.clone());
+        DocumentosContract::exportar_datos(env.clone(), id_exportacion, formato.clone(), hash.clone(), filas);
     });
 
     // Validar exportación
@@ -415,13 +1147,37 @@ fn test_exportar_datos() {
     });
     
     assert!(exportacion.is_some());
-    assert_eq!(exportacion.unwrap(), formato);
+    
+    let exportacion = exportacion.unwrap();
+    assert_eq!(exportacion.formato, formato);
+    assert_eq!(exportacion.hash, hash);
+    assert_eq!(exportacion.filas, filas);
+}
+
+#[test]
+#[should_panic(expected = "Exportación con ese ID ya existe")]
+fn test_exportar_datos_duplicado() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+
+    let formato = String::from_str(&env, "CSV");
+    let hash = BytesN::from_array(&env, &[7; 32]);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::exportar_datos(env.clone(), 1, formato.clone(), hash.clone(), 12);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::exportar_datos(env.clone(), 1, formato, hash, 3);
+    });
 }
 
 #[test]
 fn test_flujo_completo_documento() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
 
     // 1. Registrar documento
     let id_documento = 1;
@@ -430,19 +1186,20 @@ fn test_flujo_completo_documento() {
     let fecha = 1640995200_u64;
 
     env.as_contract(&contract_id, || {
-        DocumentosContract::registrar_documento(env.clone(), id_documento, titulo.clone(), estado_inicial, fecha);
+        DocumentosContract::registrar_documento(env.clone(), id_documento, registrador.clone(), titulo.clone(), estado_inicial, fecha);
     });
 
-    // 2. Verificar documento
-    let resultado_verificacion = String::from_str(&env, "Documento verificado");
-    env.as_contract(&contract_id, || {
-        DocumentosContract::verificar_documento(env.clone(), id_documento, resultado_verificacion.clone());
+    // 2. Verificar documento: pendiente todavía no es válido
+    let verificador = Address::generate(&env);
+    let resultado_verificacion = env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), id_documento, verificador.clone(), None)
     });
+    assert_eq!(resultado_verificacion.motivos, vec![&env, MotivoRechazo::NoVigente]);
 
     // 3. Actualizar estado
     let estado_final = String::from_str(&env, "Validado");
     env.as_contract(&contract_id, || {
-        DocumentosContract::actualizar_estado(env.clone(), id_documento, estado_final.clone());
+        DocumentosContract::actualizar_estado(env.clone(), id_documento, registrador.clone(), estado_final.clone());
     });
 
     // 4. Realizar respaldo
@@ -450,7 +1207,7 @@ fn test_flujo_completo_documento() {
     let ubicacion = String::from_str(&env, "/backup/cert_001.pdf");
     let autor = String::from_str(&env, "Sistema");
     env.as_contract(&contract_id, || {
-        DocumentosContract::realizar_respaldo(env.clone(), id_respaldo, fecha, ubicacion.clone(), autor.clone());
+        DocumentosContract::realizar_respaldo(env.clone(), id_respaldo, ubicacion.clone(), autor.clone());
     });
 
     // 5. Verificar estado final del documento
@@ -464,15 +1221,13 @@ fn test_flujo_completo_documento() {
     assert_eq!(titulo_final, titulo);
     assert_eq!(estado_final_obtenido, estado_final);
 
-    // 6. Verificar historial
-    let historial = env.as_contract(&contract_id, || {
-        DocumentosContract::obtener_historial(env.clone(), id_documento)
+    // 6. Verificar el registro de verificaciones
+    let verificaciones = env.as_contract(&contract_id, || {
+        DocumentosContract::obtener_verificaciones(env.clone(), id_documento)
     });
     
-    assert!(historial.is_some());
-    
-    let (_, resultado_historial) = historial.unwrap();
-    assert_eq!(resultado_historial, resultado_verificacion);
+    assert_eq!(verificaciones.len(), 1);
+    assert_eq!(verificaciones.get(0).unwrap().verificador, verificador);
 
     // 7. Verificar respaldo
     let respaldo = env.as_contract(&contract_id, || {
@@ -481,15 +1236,19 @@ fn test_flujo_completo_documento() {
     
     assert!(respaldo.is_some());
     
-    let (_, ubicacion_respaldo, autor_respaldo) = respaldo.unwrap();
-    assert_eq!(ubicacion_respaldo, ubicacion);
-    assert_eq!(autor_respaldo, autor);
+    let respaldo = respaldo.unwrap();
+    assert_eq!(respaldo.ubicacion, ubicacion);
+    assert_eq!(respaldo.autor, autor);
+    assert_eq!(respaldo.total_documentos, 1);
 }
 
 #[test]
 fn test_multiples_documentos() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, DocumentosContract);
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
 
     // Registrar múltiples documentos
     let titulos = [
@@ -506,7 +1265,7 @@ fn test_multiples_documentos() {
         let fecha = 1640995200_u64 + (i as u64 * 86400); // Un día de diferencia
 
         env.as_contract(&contract_id, || {
-            DocumentosContract::registrar_documento(env.clone(), i, titulo, estado, fecha);
+            DocumentosContract::registrar_documento(env.clone(), i, registrador.clone(), titulo, estado, fecha);
         });
     }
 
@@ -523,4 +1282,1520 @@ fn test_multiples_documentos() {
         DocumentosContract::obtener_documento(env.clone(), 99)
     });
     assert!(documento_inexistente.is_none());
-}
\ No newline at end of file
+}
+#[test]
+fn test_sincronizar_documento() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+
+    let operador = integracion_siia(&env, &contract_id);
+    let id_externo = String::from_str(&env, "ALU-2020-0042/ING-SIS");
+    let titulo = String::from_str(&env, "Ingeniería en Sistemas");
+    let activo = String::from_str(&env, "Activo");
+    let fecha = 1640995200_u64;
+
+    // Primera sincronización: crea y vincula el documento
+    let id_documento = env.as_contract(&contract_id, || {
+        DocumentosContract::sincronizar_documento(env.clone(), 1, id_externo.clone(), 10, titulo.clone(), activo.clone(), fecha, 5)
+    });
+    assert_eq!(id_documento, 10);
+
+    // Repetir la misma sincronización no duplica nada
+    let id_repetido = env.as_contract(&contract_id, || {
+        DocumentosContract::sincronizar_documento(env.clone(), 1, id_externo.clone(), 10, titulo.clone(), activo.clone(), fecha, 5)
+    });
+    assert_eq!(id_repetido, 10);
+
+    // Un cambio posterior actualiza el documento vinculado, aunque se proponga otro ID
+    let revocado = String::from_str(&env, "Revocado");
+    let id_actualizado = env.as_contract(&contract_id, || {
+        DocumentosContract::sincronizar_documento(env.clone(), 1, id_externo.clone(), 11, titulo.clone(), revocado.clone(), fecha, 9)
+    });
+    assert_eq!(id_actualizado, 10);
+
+    let (documento, otro, vinculo, externo, integracion) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::obtener_documento(env.clone(), 10),
+            DocumentosContract::obtener_documento(env.clone(), 11),
+            DocumentosContract::obtener_id_externo(env.clone(), 10),
+            DocumentosContract::buscar_por_id_externo(env.clone(), 1, id_externo.clone()),
+            DocumentosContract::verificar_integracion(env.clone(), 1).unwrap(),
+        )
+    });
+    assert_eq!(documento, Some((titulo, revocado.clone(), fecha)));
+    assert!(otro.is_none());
+    assert_eq!(vinculo, Some(IdExterno { id_integracion: 1, id_externo: id_externo.clone() }));
+    assert_eq!(externo, Some(10));
+    assert_eq!(integracion.operador, operador);
+    assert_eq!(integracion.id_institucion, 1);
+    assert_eq!(integracion.ultimo_cursor, 9);
+    assert_eq!(integracion.total_sincronizados, 1);
+
+    // Un cambio de título queda como enmienda del documento vinculado
+    let corregido = String::from_str(&env, "Ingeniería en Sistemas Computacionales");
+    env.as_contract(&contract_id, || {
+        DocumentosContract::sincronizar_documento(env.clone(), 1, id_externo.clone(), 10, corregido.clone(), revocado.clone(), fecha, 12);
+    });
+    let versiones = env.as_contract(&contract_id, || {
+        DocumentosContract::obtener_versiones(env.clone(), 10)
+    });
+    assert_eq!(versiones.len(), 2);
+    let vigente = versiones.get(1).unwrap();
+    assert_eq!(vigente.titulo, corregido);
+    assert_eq!(vigente.motivo, String::from_str(&env, "Sincronización con el sistema académico"));
+}
+
+/// Registra la institución 1 y su integración 1 ("SIIA"); devuelve el operador.
+fn integracion_siia(env: &Env, contract_id: &Address) -> Address {
+    let firmante = alta_usuario(env, contract_id, "Emisor");
+    let operador = alta_usuario(env, contract_id, "Operador");
+    env.as_contract(contract_id, || {
+        DocumentosContract::registrar_institucion(env.clone(), 1, String::from_str(env, "UNAM"), firmante.clone());
+    });
+    env.as_contract(contract_id, || {
+        DocumentosContract::registrar_integracion(env.clone(), 1, String::from_str(env, "SIIA"), operador.clone(), 1);
+    });
+    operador
+}
+
+#[test]
+#[should_panic(expected = "Cursor anterior a la última sincronización")]
+fn test_sincronizar_documento_cursor_antiguo() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    integracion_siia(&env, &contract_id);
+
+    let id_externo = String::from_str(&env, "ALU-1");
+    let titulo = String::from_str(&env, "Diploma");
+    env.as_contract(&contract_id, || {
+        DocumentosContract::sincronizar_documento(env.clone(), 1, id_externo.clone(), 1, titulo.clone(), String::from_str(&env, "Activo"), 1640995200, 9);
+    });
+
+    // Un cambio reenviado fuera de orden no puede pisar uno más reciente
+    env.as_contract(&contract_id, || {
+        DocumentosContract::sincronizar_documento(env.clone(), 1, id_externo, 1, titulo, String::from_str(&env, "Revocado"), 1640995200, 3);
+    });
+}
+
+#[test]
+#[should_panic(expected = "El documento pertenece a otra institución")]
+fn test_sincronizar_documento_otra_institucion() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    integracion_siia(&env, &contract_id);
+
+    let otra = alta_usuario(&env, &contract_id, "Emisor");
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_institucion(env.clone(), 2, String::from_str(&env, "Otra Universidad"), otra.clone());
+        DocumentosContract::sincronizar_documento(env.clone(), 1, String::from_str(&env, "ALU-1"), 1, String::from_str(&env, "Diploma"), String::from_str(&env, "Activo"), 1640995200, 1);
+    });
+
+    // Sólo la institución de la integración puede emitir lo que sincroniza
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(
+            env.clone(),
+            1,
+            otra.clone(),
+            Some(2),
+            BytesN::from_array(&env, &[1; 32]),
+            BytesN::from_array(&env, &[2; 32]),
+            None,
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "Integración no encontrada")]
+fn test_sincronizar_documento_integracion_inexistente() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::sincronizar_documento(
+            env.clone(),
+            99,
+            String::from_str(&env, "ALU-1"),
+            1,
+            String::from_str(&env, "Diploma"),
+            String::from_str(&env, "Activo"),
+            1640995200,
+            1,
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "Integración con ese ID ya existe")]
+fn test_registrar_integracion_duplicada() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+
+    let operador = integracion_siia(&env, &contract_id);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_integracion(env.clone(), 1, String::from_str(&env, "SIIA"), operador.clone(), 1);
+    });
+}
+
+#[test]
+fn test_registrar_insignia() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let titulo = String::from_str(&env, "Taller de Rust");
+    let estado = String::from_str(&env, "Activo");
+    let fecha = 1640995200_u64;
+    let datos = Insignia {
+        logro: String::from_str(&env, "Rust Básico"),
+        criterios: String::from_str(&env, "Asistir a 20 horas y aprobar el proyecto final"),
+        hash_imagen: BytesN::from_array(&env, &[9; 32]),
+    };
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_insignia(
+            env.clone(),
+            1,
+            registrador.clone(),
+            titulo.clone(),
+            estado.clone(),
+            fecha,
+            datos.clone(),
+        );
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(
+            env.clone(),
+            2,
+            registrador.clone(),
+            titulo.clone(),
+            estado.clone(),
+            fecha,
+        );
+    });
+
+    let (documento, insignia, no_insignia) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::obtener_documento(env.clone(), 1),
+            DocumentosContract::obtener_insignia(env.clone(), 1),
+            DocumentosContract::obtener_insignia(env.clone(), 2),
+        )
+    });
+    assert_eq!(documento, Some((titulo, estado, fecha)));
+    assert_eq!(insignia, Some(datos));
+    assert!(no_insignia.is_none());
+}
+
+#[test]
+fn test_verificar_documento_publico() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let hash_documento = BytesN::from_array(&env, &[3; 32]);
+    let hash_titular = BytesN::from_array(&env, &[7; 32]);
+    let activo = String::from_str(&env, "Activo");
+    env.ledger().set_timestamp(1_700_000_000);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Ingeniería en Sistemas"), activo.clone(), 1640995200);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 2, registrador.clone(), String::from_str(&env, "Curso de Inglés B2"), activo.clone(), 1640995200);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, hash_documento.clone(), hash_titular.clone(), None);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 2, emisor.clone(), None, hash_documento.clone(), hash_titular.clone(), Some(1_800_000_000));
+    });
+
+    let (titulo, certificado, inexistente) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::verificar_documento_publico(env.clone(), 1, None),
+            DocumentosContract::verificar_documento_publico(env.clone(), 2, None),
+            DocumentosContract::verificar_documento_publico(env.clone(), 99, None),
+        )
+    });
+    assert_eq!(
+        titulo,
+        ResultadoVerificacion {
+            id_documento: 1,
+            valido: true,
+            motivos: vec![&env],
+            estado: Some(activo.clone()),
+            emisor: Some(emisor.clone()),
+            hash_titular: Some(hash_titular.clone().into()),
+            vencimiento: None,
+        }
+    );
+    assert!(certificado.valido);
+    assert_eq!(certificado.vencimiento, Some(1_800_000_000));
+    assert_eq!(inexistente.motivos, vec![&env, MotivoRechazo::NoEncontrado]);
+    assert!(inexistente.estado.is_none());
+    assert!(inexistente.emisor.is_none());
+
+    // Al vencer deja de ser válido; una revocación invalida aunque no venza
+    env.ledger().set_timestamp(1_800_000_000);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::actualizar_estado(env.clone(), 1, emisor.clone(), String::from_str(&env, "Revocado"));
+    });
+    let (titulo, certificado) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::verificar_documento_publico(env.clone(), 1, None),
+            DocumentosContract::verificar_documento_publico(env.clone(), 2, None),
+        )
+    });
+    assert_eq!(titulo.motivos, vec![&env, MotivoRechazo::Revocado]);
+    assert_eq!(titulo.estado, Some(String::from_str(&env, "Revocado")));
+    assert_eq!(certificado.motivos, vec![&env, MotivoRechazo::Vencido]);
+
+    // El hash presentado debe ser el del archivo emitido
+    let (coincide, no_coincide) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::verificar_documento_publico(env.clone(), 2, Some(hash_documento.clone())),
+            DocumentosContract::verificar_documento_publico(env.clone(), 2, Some(hash_titular.clone())),
+        )
+    });
+    assert_eq!(coincide.motivos, vec![&env, MotivoRechazo::Vencido]);
+    assert_eq!(no_coincide.motivos, vec![&env, MotivoRechazo::Vencido, MotivoRechazo::HashNoCoincide]);
+
+    // Si el emisor se desactiva, sus documentos dejan de ser válidos
+    env.as_contract(&contract_id, || {
+        DocumentosContract::desactivar_emisor(env.clone(), emisor.clone());
+    });
+    let titulo = env.as_contract(&contract_id, || {
+        DocumentosContract::verificar_documento_publico(env.clone(), 1, None)
+    });
+    assert_eq!(titulo.motivos, vec![&env, MotivoRechazo::Revocado, MotivoRechazo::EmisorInactivo]);
+}
+
+#[test]
+#[should_panic(expected = "Emisión ya registrada")]
+fn test_registrar_emision_duplicada() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let hash_documento = BytesN::from_array(&env, &[3; 32]);
+    let hash_titular = BytesN::from_array(&env, &[7; 32]);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Ingeniería en Sistemas"), String::from_str(&env, "Activo"), 1640995200);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, hash_documento.clone(), hash_titular.clone(), None);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, hash_documento.clone(), hash_titular.clone(), None);
+    });
+}
+
+/// Documento 1 con datos de emisión y una calificación privada de `titular`.
+fn registro_con_campos_privados(env: &Env, titular: &Address) -> Address {
+    let contract_id = desplegar(env);
+    let registrador = alta_usuario(env, &contract_id, "Registrador");
+    let emisor = alta_usuario(env, &contract_id, "Emisor");
+    let campos = map![env, (symbol_short!("promedio"), Bytes::from_slice(env, b"cifrado:9.4"))];
+    env.ledger().set_timestamp(1_700_000_000);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(env, "Ingeniería en Sistemas"), String::from_str(env, "Activo"), 1640995200);
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, BytesN::from_array(env, &[3; 32]), BytesN::from_array(env, &[7; 32]), None);
+        DocumentosContract::registrar_campos_privados(env.clone(), 1, titular.clone(), campos);
+    });
+    contract_id
+}
+
+#[test]
+fn test_acceso_campos_privados() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let titular = Address::generate(&env);
+    let empleador = Address::generate(&env);
+    let contract_id = registro_con_campos_privados(&env, &titular);
+    env.ledger().set_timestamp(1_700_000_000);
+
+    let propios = env.as_contract(&contract_id, || {
+        DocumentosContract::obtener_campos_privados(env.clone(), 1, titular.clone())
+    });
+    assert_eq!(propios.get(symbol_short!("promedio")), Some(Bytes::from_slice(&env, b"cifrado:9.4")));
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::otorgar_acceso(env.clone(), 1, empleador.clone(), 1_700_086_400);
+    });
+    let compartidos = env.as_contract(&contract_id, || {
+        DocumentosContract::obtener_campos_privados(env.clone(), 1, empleador.clone())
+    });
+    assert_eq!(compartidos, propios);
+
+    // Un plazo que ya pasó no concede nada
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    assert_eq!(
+        cliente.try_otorgar_acceso(&1, &Address::generate(&env), &1_700_000_000),
+        Err(Ok(ErrorValidacion::VencimientoPasado.into()))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Acceso no autorizado")]
+fn test_acceso_campos_privados_vencido() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let titular = Address::generate(&env);
+    let empleador = Address::generate(&env);
+    let contract_id = registro_con_campos_privados(&env, &titular);
+
+    env.ledger().set_timestamp(1_700_000_000);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::otorgar_acceso(env.clone(), 1, empleador.clone(), 1_700_086_400);
+    });
+    env.ledger().set_timestamp(1_700_086_400);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::obtener_campos_privados(env.clone(), 1, empleador.clone());
+    });
+}
+
+#[test]
+#[should_panic(expected = "Acceso no autorizado")]
+fn test_acceso_campos_privados_revocado() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let titular = Address::generate(&env);
+    let empleador = Address::generate(&env);
+    let contract_id = registro_con_campos_privados(&env, &titular);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::otorgar_acceso(env.clone(), 1, empleador.clone(), u64::MAX);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::revocar_acceso(env.clone(), 1, empleador.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::obtener_campos_privados(env.clone(), 1, empleador.clone());
+    });
+}
+
+#[test]
+fn test_verificar_campo() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+
+    // Compromiso del campo "carrera" con su sal: sha256(sal || valor)
+    let sal = BytesN::from_array(&env, &[5; 32]);
+    let mut datos = Bytes::from(sal.clone());
+    datos.extend_from_slice("Ingeniería".as_bytes());
+    let compromiso = env.crypto().sha256(&datos).to_bytes();
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Título profesional"), String::from_str(&env, "Activo"), 1640995200);
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, BytesN::from_array(&env, &[3; 32]), BytesN::from_array(&env, &[7; 32]), None);
+        DocumentosContract::registrar_compromisos(env.clone(), 1, map![&env, (symbol_short!("carrera"), compromiso)]);
+    });
+
+    let verificar = |campo, valor: &str, sal: &BytesN<32>| {
+        env.as_contract(&contract_id, || {
+            DocumentosContract::verificar_campo(env.clone(), 1, campo, String::from_str(&env, valor), sal.clone())
+        })
+    };
+    assert!(verificar(symbol_short!("carrera"), "Ingeniería", &sal));
+    assert!(!verificar(symbol_short!("carrera"), "Medicina", &sal));
+    assert!(!verificar(symbol_short!("carrera"), "Ingeniería", &BytesN::from_array(&env, &[6; 32])));
+    // Un campo sin compromiso (p. ej. el promedio) no se puede comprobar
+    assert!(!verificar(symbol_short!("promedio"), "9.4", &sal));
+    // Un valor más largo que cualquier campo comprometido no coincide
+    let largo = [b'x'; 300];
+    assert!(!verificar(symbol_short!("carrera"), core::str::from_utf8(&largo).unwrap(), &sal));
+}
+
+#[test]
+fn test_rotar_clave_institucion() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let clave_antigua = alta_usuario(&env, &contract_id, "Emisor");
+    let clave_nueva = alta_usuario(&env, &contract_id, "Emisor");
+    let titulo = String::from_str(&env, "Diploma");
+    let activo = String::from_str(&env, "Activo");
+    let hash = BytesN::from_array(&env, &[3; 32]);
+
+    env.ledger().set_timestamp(1_000);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_institucion(env.clone(), 1, String::from_str(&env, "Universidad de Prueba"), clave_antigua.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), titulo.clone(), activo.clone(), 1_000);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 2, registrador.clone(), titulo.clone(), activo.clone(), 1_000);
+    });
+    env.ledger().set_timestamp(2_000);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 1, clave_antigua.clone(), Some(1), hash.clone(), hash.clone(), None);
+    });
+
+    env.ledger().set_timestamp(3_000);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::rotar_clave(env.clone(), 1, clave_nueva.clone());
+    });
+    env.ledger().set_timestamp(4_000);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 2, clave_nueva.clone(), Some(1), hash.clone(), hash.clone(), None);
+    });
+
+    let institucion = env.as_contract(&contract_id, || {
+        DocumentosContract::obtener_institucion(env.clone(), 1).unwrap()
+    });
+    assert_eq!(
+        institucion.claves,
+        vec![
+            &env,
+            ClaveFirma { direccion: clave_antigua.clone(), desde: 1_000, hasta: Some(3_000) },
+            ClaveFirma { direccion: clave_nueva.clone(), desde: 3_000, hasta: None },
+        ]
+    );
+
+    // Lo emitido con la clave antigua antes de la rotación sigue siendo válido
+    let (antiguo, nuevo, antigua_hoy, antigua_antes) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::verificar_documento_publico(env.clone(), 1, None),
+            DocumentosContract::verificar_documento_publico(env.clone(), 2, None),
+            DocumentosContract::clave_autorizada(env.clone(), 1, clave_antigua.clone(), 4_000),
+            DocumentosContract::clave_autorizada(env.clone(), 1, clave_antigua.clone(), 2_000),
+        )
+    });
+    assert!(antiguo.valido);
+    assert!(nuevo.valido);
+    assert!(!antigua_hoy);
+    assert!(antigua_antes);
+}
+
+#[test]
+fn test_recuperar_clave_institucion() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let comprometida = alta_usuario(&env, &contract_id, "Emisor");
+    let nueva = alta_usuario(&env, &contract_id, "Emisor");
+    let hash = BytesN::from_array(&env, &[3; 32]);
+    let activo = String::from_str(&env, "Activo");
+
+    cliente.registrar_institucion(&1, &String::from_str(&env, "Universidad de Prueba"), &comprometida);
+    cliente.registrar_documento(&1, &registrador, &String::from_str(&env, "Diploma"), &activo, &1640995200);
+    cliente.registrar_emision(&1, &comprometida, &Some(1), &hash, &hash, &None);
+
+    // La clave comprometida pertenece a un usuario dado de baja: no puede rotar
+    cliente.desactivar_usuario(&comprometida);
+    assert!(cliente.try_rotar_clave(&1, &nueva).is_err());
+
+    // El administrador la sustituye sin ella
+    env.ledger().set_timestamp(1_700_000_100);
+    let admin = cliente.obtener_admin();
+    cliente.recuperar_clave(&1, &nueva);
+    assert!(!env.auths().iter().any(|(direccion, _)| *direccion == comprometida));
+    assert!(env.auths().iter().any(|(direccion, _)| *direccion == admin));
+    assert_eq!(
+        cliente.obtener_institucion(&1).unwrap().claves,
+        vec![
+            &env,
+            ClaveFirma { direccion: comprometida.clone(), desde: 1_700_000_000, hasta: Some(1_700_000_100) },
+            ClaveFirma { direccion: nueva.clone(), desde: 1_700_000_100, hasta: None },
+        ]
+    );
+    assert!(!cliente.clave_autorizada(&1, &comprometida, &1_700_000_100));
+    assert!(cliente.clave_autorizada(&1, &nueva, &1_700_000_100));
+    // Lo emitido antes sigue valiendo, y la nueva clave responde de ello
+    assert!(cliente.verificar_documento_publico(&1, &None).valido);
+    cliente.actualizar_estado(&1, &nueva, &String::from_str(&env, "Revocado"));
+
+    // Sin el administrador no hay recuperación
+    env.mock_auths(&[]);
+    assert!(cliente.try_recuperar_clave(&1, &registrador).is_err());
+}
+
+#[test]
+fn test_registrar_institucion_requiere_admin() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let firmante = Address::generate(&env);
+    let nombre = String::from_str(&env, "Universidad Falsa");
+
+    // La firma de la propia institución no basta sin la del administrador
+    env.mock_auths(&[MockAuth {
+        address: &firmante,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "registrar_institucion",
+            args: (1, nombre.clone(), firmante.clone()).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(cliente.try_registrar_institucion(&1, &nombre, &firmante).is_err());
+    assert!(cliente.obtener_institucion(&1).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Emisor no autorizado por la institución")]
+fn test_registrar_emision_clave_rotada() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let clave_antigua = alta_usuario(&env, &contract_id, "Emisor");
+    let hash = BytesN::from_array(&env, &[3; 32]);
+
+    env.ledger().set_timestamp(1_000);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_institucion(env.clone(), 1, String::from_str(&env, "Universidad de Prueba"), clave_antigua.clone());
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Diploma"), String::from_str(&env, "Activo"), 1_000);
+    });
+    env.ledger().set_timestamp(3_000);
+    let clave_nueva = alta_usuario(&env, &contract_id, "Emisor");
+    env.as_contract(&contract_id, || {
+        DocumentosContract::rotar_clave(env.clone(), 1, clave_nueva.clone());
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 1, clave_antigua.clone(), Some(1), hash.clone(), hash.clone(), None);
+    });
+}
+
+#[test]
+fn test_enmendar_documento() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let original = String::from_str(&env, "Licenciatura en Derecho - Ana Pérez");
+    let corregido = String::from_str(&env, "Licenciatura en Derecho - Ana Pérez Gómez");
+    let activo = String::from_str(&env, "Activo");
+    env.ledger().set_timestamp(1_700_000_000);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), original.clone(), activo.clone(), 1640995200);
+    });
+    let versiones = env.as_contract(&contract_id, || DocumentosContract::obtener_versiones(env.clone(), 1));
+    assert_eq!(versiones.len(), 1);
+
+    let version = env.as_contract(&contract_id, || {
+        DocumentosContract::enmendar_documento(
+            env.clone(),
+            1,
+            registrador.clone(),
+            Cambios { titulo: Some(corregido.clone()), fecha_emision_academica: None },
+            String::from_str(&env, "Cambio de apellido por matrimonio"),
+        )
+    });
+    assert_eq!(version.numero, 2);
+    assert_eq!(version.anterior, Some(1));
+    assert_eq!(version.registrada, 1_700_000_000);
+
+    let (documento, versiones) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::obtener_documento(env.clone(), 1),
+            DocumentosContract::obtener_versiones(env.clone(), 1),
+        )
+    });
+    assert_eq!(documento, Some((corregido.clone(), activo, 1640995200)));
+    assert_eq!(versiones.len(), 2);
+    let primera = versiones.get(0).unwrap();
+    assert_eq!(primera.titulo, original);
+    assert!(primera.reemplazada);
+    assert_eq!(versiones.get(1).unwrap(), version);
+    assert!(!version.reemplazada);
+}
+
+#[test]
+#[should_panic(expected = "Enmienda sin cambios")]
+fn test_enmendar_documento_sin_cambios() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let titulo = String::from_str(&env, "Diploma");
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), titulo.clone(), String::from_str(&env, "Activo"), 1640995200);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::enmendar_documento(
+            env.clone(),
+            1,
+            registrador.clone(),
+            Cambios { titulo: Some(titulo.clone()), fecha_emision_academica: None },
+            String::from_str(&env, "Errata"),
+        );
+    });
+}
+
+#[test]
+fn test_enmendar_documento_requiere_responsable() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let activo = String::from_str(&env, "Activo");
+    let motivo = String::from_str(&env, "Errata");
+    let cambios = Cambios { titulo: Some(String::from_str(&env, "Diploma corregido")), fecha_emision_academica: None };
+
+    cliente.registrar_documento(&1, &registrador, &String::from_str(&env, "Diploma"), &activo, &1640995200);
+    cliente.registrar_documento(&2, &registrador, &String::from_str(&env, "Diploma"), &activo, &1640995200);
+    cliente.registrar_emision(&2, &emisor, &None, &hash, &hash, &None);
+
+    // Sin perfil no se puede enmendar ni un documento sin emitir
+    assert!(cliente.try_enmendar_documento(&1, &Address::generate(&env), &cambios, &motivo).is_err());
+    // Emitido, sólo quien responde de la emisión
+    assert!(cliente.try_enmendar_documento(&2, &registrador, &cambios, &motivo).is_err());
+    assert_eq!(cliente.enmendar_documento(&2, &emisor, &cambios, &motivo).numero, 2);
+
+    // Y siempre con la firma del responsable
+    env.mock_auths(&[]);
+    assert!(cliente.try_enmendar_documento(&1, &registrador, &cambios, &motivo).is_err());
+    assert_eq!(cliente.obtener_versiones(&1).len(), 1);
+}
+
+fn tipo_diploma(env: &Env, politica_firma: PoliticaFirma) -> TipoDocumento {
+    TipoDocumento {
+        nombre: String::from_str(env, "Diploma"),
+        campos_requeridos: vec![env, symbol_short!("programa"), symbol_short!("titular")],
+        vigencia: Some(31_536_000),
+        politica_firma,
+    }
+}
+
+#[test]
+#[should_panic(expected = "Tipo con ese ID ya existe")]
+fn test_registrar_tipo_duplicado() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+
+    cliente.registrar_tipo(&1, &tipo_diploma(&env, PoliticaFirma::Ninguna));
+    // Endurecer la política después cambiaría lo que verifican los ya emitidos
+    cliente.registrar_tipo(&1, &tipo_diploma(&env, PoliticaFirma::Institucional));
+}
+
+#[test]
+fn test_registrar_documento_tipado() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let campos = map![
+        &env,
+        (symbol_short!("programa"), String::from_str(&env, "Ingeniería Civil")),
+        (symbol_short!("titular"), String::from_str(&env, "Luis Rojas")),
+    ];
+    env.ledger().set_timestamp(1_700_000_000);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_tipo(env.clone(), 1, tipo_diploma(&env, PoliticaFirma::Emisor));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento_tipado(
+            env.clone(),
+            10,
+            registrador.clone(),
+            1,
+            String::from_str(&env, "Diploma de Ingeniería Civil"),
+            String::from_str(&env, "Activo"),
+            1640995200,
+            campos.clone(),
+        );
+    });
+
+    let (tipado, sin_firma) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::obtener_documento_tipado(env.clone(), 10),
+            DocumentosContract::verificar_documento_publico(env.clone(), 10, None),
+        )
+    });
+    assert_eq!(tipado, Some(DocumentoTipado { id_tipo: 1, campos }));
+    assert!(!sin_firma.valido);
+    assert_eq!(sin_firma.motivos, vec![&env, MotivoRechazo::SinFirma]);
+
+    // Sin vencimiento explícito se aplica la vigencia del tipo.
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 10, emisor.clone(), None, hash.clone(), hash.clone(), None);
+    });
+    let firmado = env.as_contract(&contract_id, || {
+        DocumentosContract::verificar_documento_publico(env.clone(), 10, None)
+    });
+    assert!(firmado.valido);
+    assert_eq!(firmado.vencimiento, Some(1_700_000_000 + 31_536_000));
+}
+
+#[test]
+#[should_panic(expected = "Falta un campo requerido por el tipo de documento")]
+fn test_registrar_documento_tipado_sin_campo() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_tipo(env.clone(), 1, tipo_diploma(&env, PoliticaFirma::Ninguna));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento_tipado(
+            env.clone(),
+            10,
+            registrador.clone(),
+            1,
+            String::from_str(&env, "Diploma"),
+            String::from_str(&env, "Activo"),
+            1640995200,
+            map![&env, (symbol_short!("programa"), String::from_str(&env, "Medicina"))],
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "El tipo de documento exige firma institucional")]
+fn test_registrar_emision_politica_institucional() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_tipo(env.clone(), 1, tipo_diploma(&env, PoliticaFirma::Institucional));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento_tipado(
+            env.clone(),
+            10,
+            registrador.clone(),
+            1,
+            String::from_str(&env, "Diploma"),
+            String::from_str(&env, "Activo"),
+            1640995200,
+            map![
+                &env,
+                (symbol_short!("programa"), String::from_str(&env, "Medicina")),
+                (symbol_short!("titular"), String::from_str(&env, "Eva Díaz")),
+            ],
+        );
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_emision(env.clone(), 10, emisor.clone(), None, hash.clone(), hash.clone(), None);
+    });
+}
+
+#[test]
+fn test_establecer_atributo() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    env.ledger().set_timestamp(1_700_000_000);
+
+    let programa = String::from_str(&env, "Ingeniería Industrial");
+    let creditos = String::from_str(&env, "240");
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Título"), String::from_str(&env, "Activo"), 1640995200);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::establecer_atributo(env.clone(), 1, registrador.clone(), symbol_short!("programa"), Some(programa.clone()));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::establecer_atributo(env.clone(), 1, registrador.clone(), symbol_short!("creditos"), Some(String::from_str(&env, "230")));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::establecer_atributo(env.clone(), 1, registrador.clone(), symbol_short!("creditos"), Some(creditos.clone()));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::establecer_atributo(env.clone(), 1, registrador.clone(), symbol_short!("idioma"), Some(String::from_str(&env, "es")));
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::establecer_atributo(env.clone(), 1, registrador.clone(), symbol_short!("idioma"), None);
+    });
+
+    let (atributos, cambios) = env.as_contract(&contract_id, || {
+        (
+            DocumentosContract::obtener_atributos(env.clone(), 1),
+            DocumentosContract::obtener_cambios_atributos(env.clone(), 1),
+        )
+    });
+    assert_eq!(
+        atributos,
+        map![&env, (symbol_short!("creditos"), creditos.clone()), (symbol_short!("programa"), programa)]
+    );
+    assert_eq!(cambios.len(), 5);
+    assert_eq!(
+        cambios.get(2).unwrap(),
+        CambioAtributo {
+            clave: symbol_short!("creditos"),
+            anterior: Some(String::from_str(&env, "230")),
+            valor: Some(creditos),
+            fecha: 1_700_000_000,
+        }
+    );
+    assert_eq!(cambios.get(4).unwrap().valor, None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_establecer_atributo_sin_firma() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    cliente.registrar_documento(&1, &registrador, &String::from_str(&env, "Título"), &String::from_str(&env, "Activo"), &1640995200);
+
+    // Aunque el documento no esté emitido, el responsable tiene que firmar
+    env.mock_auths(&[]);
+    cliente.establecer_atributo(&1, &registrador, &symbol_short!("programa"), &Some(String::from_str(&env, "Derecho")));
+}
+
+#[test]
+#[should_panic(expected = "Demasiados atributos")]
+fn test_establecer_atributo_limite() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let claves = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q"];
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Título"), String::from_str(&env, "Activo"), 1640995200);
+    });
+    for clave in claves {
+        env.as_contract(&contract_id, || {
+            DocumentosContract::establecer_atributo(
+                env.clone(),
+                1,
+                registrador.clone(),
+                soroban_sdk::Symbol::new(&env, clave),
+                Some(String::from_str(&env, "sí")),
+            );
+        });
+    }
+}
+
+#[test]
+fn test_validacion_entradas() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let client = DocumentosContractClient::new(&env, &contract_id);
+
+    let titulo = String::from_str(&env, "Constancia de Estudios");
+    let activo = String::from_str(&env, "Activo");
+    let vacio = String::from_str(&env, "");
+    let largo = String::from_str(&env, &"x".repeat(201));
+
+    assert_eq!(
+        client.try_registrar_documento(&1, &registrador, &vacio, &activo, &1640995200),
+        Err(Ok(ErrorValidacion::TituloVacio.into()))
+    );
+    assert_eq!(
+        client.try_registrar_documento(&1, &registrador, &largo, &activo, &1640995200),
+        Err(Ok(ErrorValidacion::TituloDemasiadoLargo.into()))
+    );
+    assert_eq!(
+        client.try_registrar_documento(&1, &registrador, &titulo, &vacio, &1640995200),
+        Err(Ok(ErrorValidacion::EstadoVacio.into()))
+    );
+    assert_eq!(
+        client.try_registrar_documento(&1, &registrador, &titulo, &activo, &0),
+        Err(Ok(ErrorValidacion::FechaNula.into()))
+    );
+    assert_eq!(
+        client.try_registrar_documento(&1, &registrador, &titulo, &activo, &1_700_086_400),
+        Err(Ok(ErrorValidacion::FechaFutura.into()))
+    );
+    // Se toleran pequeños adelantos de reloj.
+    client.registrar_documento(&1, &registrador, &titulo, &activo, &1_700_000_060);
+
+    assert_eq!(
+        client.try_crear_usuario(&Address::generate(&env), &Some(vacio.clone()), &None, &String::from_str(&env, "Registrador")),
+        Err(Ok(ErrorValidacion::NombreVacio.into()))
+    );
+    assert_eq!(
+        client.try_realizar_respaldo(&1, &vacio, &String::from_str(&env, "admin")),
+        Err(Ok(ErrorValidacion::UbicacionVacia.into()))
+    );
+    assert_eq!(client.try_actualizar_estado(&1, &registrador, &largo), Err(Ok(ErrorValidacion::EstadoDemasiadoLargo.into())));
+
+    let cambios = Cambios { titulo: Some(String::from_str(&env, "Constancia")), fecha_emision_academica: None };
+    assert_eq!(client.try_enmendar_documento(&1, &registrador, &cambios, &vacio), Err(Ok(ErrorValidacion::MotivoVacio.into())));
+    let muy_largo = String::from_str(&env, &"x".repeat(1025));
+    assert_eq!(
+        client.try_enmendar_documento(&1, &registrador, &cambios, &muy_largo),
+        Err(Ok(ErrorValidacion::MotivoDemasiadoLargo.into()))
+    );
+    assert_eq!(client.try_consulta_historial(&1, &vacio), Err(Ok(ErrorValidacion::ResultadoVacio.into())));
+    assert_eq!(
+        client.try_consulta_historial(&1, &muy_largo),
+        Err(Ok(ErrorValidacion::ResultadoDemasiadoLargo.into()))
+    );
+
+    let imagen = BytesN::from_array(&env, &[9; 32]);
+    let logro = String::from_str(&env, "Rust básico");
+    let insignia = |logro: &String, criterios: &String| Insignia { logro: logro.clone(), criterios: criterios.clone(), hash_imagen: imagen.clone() };
+    assert_eq!(
+        client.try_registrar_insignia(&2, &registrador, &titulo, &activo, &1640995200, &insignia(&vacio, &logro)),
+        Err(Ok(ErrorValidacion::LogroVacio.into()))
+    );
+    assert_eq!(
+        client.try_registrar_insignia(&2, &registrador, &titulo, &activo, &1640995200, &insignia(&largo, &logro)),
+        Err(Ok(ErrorValidacion::LogroDemasiadoLargo.into()))
+    );
+    assert_eq!(
+        client.try_registrar_insignia(&2, &registrador, &titulo, &activo, &1640995200, &insignia(&logro, &vacio)),
+        Err(Ok(ErrorValidacion::CriteriosVacios.into()))
+    );
+    assert_eq!(
+        client.try_registrar_insignia(&2, &registrador, &titulo, &activo, &1640995200, &insignia(&logro, &muy_largo)),
+        Err(Ok(ErrorValidacion::CriteriosDemasiadoLargos.into()))
+    );
+
+    let mut tipo = tipo_diploma(&env, PoliticaFirma::Ninguna);
+    tipo.nombre = vacio.clone();
+    assert_eq!(client.try_registrar_tipo(&1, &tipo), Err(Ok(ErrorValidacion::NombreVacio.into())));
+    tipo.nombre = largo.clone();
+    assert_eq!(client.try_registrar_tipo(&1, &tipo), Err(Ok(ErrorValidacion::NombreDemasiadoLargo.into())));
+
+    assert_eq!(
+        client.try_registrar_emision(&1, &Address::generate(&env), &None, &imagen, &imagen, &Some(1_700_000_000)),
+        Err(Ok(ErrorValidacion::VencimientoPasado.into()))
+    );
+    assert_eq!(
+        client.try_establecer_atributo(&1, &registrador, &soroban_sdk::Symbol::new(&env, "mencion"), &Some(String::from_str(&env, &"x".repeat(257)))),
+        Err(Ok(ErrorValidacion::ValorDemasiadoLargo.into()))
+    );
+
+    assert_eq!(client.try_exportar_datos(&1, &vacio, &imagen, &1), Err(Ok(ErrorValidacion::FormatoVacio.into())));
+    assert_eq!(
+        client.try_exportar_datos(&1, &String::from_str(&env, &"x".repeat(17)), &imagen, &1),
+        Err(Ok(ErrorValidacion::FormatoDemasiadoLargo.into()))
+    );
+
+    client.registrar_tipo(&1, &tipo_diploma(&env, PoliticaFirma::Ninguna));
+    let tipado = |campos: &Map<soroban_sdk::Symbol, String>| {
+        client.try_registrar_documento_tipado(&3, &registrador, &1, &titulo, &activo, &1640995200, campos)
+    };
+    let mut campos = map![&env, (symbol_short!("programa"), vacio.clone()), (symbol_short!("titular"), logro.clone())];
+    assert_eq!(tipado(&campos), Err(Ok(ErrorValidacion::ValorVacio.into())));
+    campos.set(symbol_short!("programa"), String::from_str(&env, &"x".repeat(257)));
+    assert_eq!(tipado(&campos), Err(Ok(ErrorValidacion::ValorDemasiadoLargo.into())));
+    campos.set(symbol_short!("programa"), logro.clone());
+    for nombre in ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o"] {
+        campos.set(soroban_sdk::Symbol::new(&env, nombre), logro.clone());
+    }
+    assert_eq!(tipado(&campos), Err(Ok(ErrorValidacion::DemasiadosCampos.into())));
+
+    assert_eq!(
+        client.try_asignar_rol(&registrador, &String::from_str(&env, "Emisor"), &Some(1_700_000_000)),
+        Err(Ok(ErrorValidacion::VencimientoPasado.into()))
+    );
+}
+
+#[test]
+fn test_tarifas_y_retiro() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let emisor_token = Address::generate(&env);
+    let activo = env.register_stellar_asset_contract_v2(emisor_token);
+    let token = token::Client::new(&env, &activo.address());
+    let administrador_token = token::StellarAssetClient::new(&env, &activo.address());
+
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let verificador = Address::generate(&env);
+    let tesoreria = Address::generate(&env);
+    administrador_token.mint(&emisor, &100);
+    administrador_token.mint(&verificador, &100);
+
+    let tarifas = Tarifas { token: activo.address(), tesoreria: tesoreria.clone(), emision: 50, verificacion: 10 };
+    env.as_contract(&contract_id, || {
+        DocumentosContract::configurar_tarifas(env.clone(), tarifas.clone());
+    });
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Diploma"), String::from_str(&env, "Activo"), 1640995200);
+        DocumentosContract::registrar_emision(env.clone(), 1, emisor.clone(), None, hash.clone(), hash.clone(), None);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 1, verificador.clone(), None);
+    });
+
+    assert_eq!(token.balance(&emisor), 50);
+    assert_eq!(token.balance(&verificador), 90);
+    assert_eq!(token.balance(&contract_id), 60);
+
+    // Lo pagado renueva la vida del almacenamiento que tocó
+    env.as_contract(&contract_id, || {
+        assert_eq!(env.storage().persistent().get_ttl(&EMISION_KEY), TTL_EXTENSION);
+        assert_eq!(env.storage().persistent().get_ttl(&DOCUMENTS_KEY), TTL_EXTENSION);
+        assert_eq!(env.storage().persistent().get_ttl(&(VERIFICA_KEY, 1)), TTL_EXTENSION);
+    });
+
+    env.as_contract(&contract_id, || {
+        DocumentosContract::retirar_fondos(env.clone(), 60);
+    });
+    assert_eq!(token.balance(&tesoreria), 60);
+    assert_eq!(token.balance(&contract_id), 0);
+    let configuradas = env.as_contract(&contract_id, || DocumentosContract::obtener_tarifas(env.clone()));
+    assert_eq!(configuradas, Some(tarifas));
+}
+
+/// Comprueba que la última llamada sólo pidió la firma del administrador.
+fn firmo_solo_admin(env: &Env, admin: &Address, contract_id: &Address, funcion: &str, args: soroban_sdk::Vec<soroban_sdk::Val>) {
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(&auths[0].0, admin);
+    assert_eq!(
+        auths[0].1.function,
+        AuthorizedFunction::Contract((contract_id.clone(), soroban_sdk::Symbol::new(env, funcion), args))
+    );
+}
+
+#[test]
+fn test_operaciones_de_admin() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_700_000_000);
+    let admin = Address::generate(&env);
+    let intruso = Address::generate(&env);
+    let contract_id = env.register(DocumentosContract, (admin.clone(),));
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    assert_eq!(cliente.obtener_admin(), admin);
+
+    let activo = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let tesoreria = Address::generate(&env);
+    env.mock_all_auths();
+    cliente.configurar_tarifas(&Tarifas { token: activo.address(), tesoreria: tesoreria.clone(), emision: 0, verificacion: 5 });
+    token::StellarAssetClient::new(&env, &activo.address()).mint(&contract_id, &10);
+
+    // Cada operación firmada sólo por otra dirección se rechaza...
+    let tipo = tipo_diploma(&env, PoliticaFirma::Ninguna);
+    let socio = Address::generate(&env);
+    env.mock_auths(&[MockAuth {
+        address: &intruso,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "registrar_tipo",
+            args: (1, tipo.clone()).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(cliente.try_registrar_tipo(&1, &tipo).is_err());
+    env.mock_auths(&[MockAuth {
+        address: &intruso,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "retirar_fondos",
+            args: (10_i128,).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(cliente.try_retirar_fondos(&10).is_err());
+    env.mock_auths(&[MockAuth {
+        address: &intruso,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "agregar_par",
+            args: (socio.clone(),).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(cliente.try_agregar_par(&socio).is_err());
+    assert!(cliente.obtener_tipo(&1).is_none());
+    assert!(cliente.pares_confiables().is_empty());
+
+    // ...y con la del administrador sale adelante, sin pedir ninguna otra
+    env.mock_all_auths();
+    cliente.registrar_tipo(&1, &tipo);
+    firmo_solo_admin(&env, &admin, &contract_id, "registrar_tipo", (1, tipo.clone()).into_val(&env));
+    cliente.retirar_fondos(&10);
+    firmo_solo_admin(&env, &admin, &contract_id, "retirar_fondos", (10_i128,).into_val(&env));
+    cliente.agregar_par(&socio);
+    firmo_solo_admin(&env, &admin, &contract_id, "agregar_par", (socio.clone(),).into_val(&env));
+    assert_eq!(token::Client::new(&env, &activo.address()).balance(&tesoreria), 10);
+}
+
+#[test]
+fn test_escrituras_requieren_perfil() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let admin = cliente.obtener_admin();
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let ajeno = Address::generate(&env);
+    let titulo = String::from_str(&env, "Diploma");
+    let activo = String::from_str(&env, "Activo");
+    let revocado = String::from_str(&env, "Revocado");
+
+    // Firmar no basta: sin perfil no se registra ni se cambia nada
+    assert!(cliente.try_registrar_documento(&1, &ajeno, &titulo, &activo, &1640995200).is_err());
+    cliente.registrar_documento(&1, &registrador, &titulo, &activo, &1640995200);
+    assert!(cliente.try_actualizar_estado(&1, &ajeno, &revocado).is_err());
+    cliente.actualizar_estado(&1, &registrador, &revocado);
+    assert_eq!(cliente.obtener_documento(&1), Some((titulo, revocado, 1640995200)));
+
+    // Respaldos, informes y exportaciones los firma el administrador
+    let ubicacion = String::from_str(&env, "s3://respaldos/1.jsonl");
+    let autor = String::from_str(&env, "Admin");
+    let rango = Rango { desde: 0, hasta: 1_700_000_000 };
+    let formato = String::from_str(&env, "csv");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    env.mock_auths(&[]);
+    assert!(cliente.try_realizar_respaldo(&1, &ubicacion, &autor).is_err());
+    assert!(cliente.try_generar_informe(&1, &TipoInforme::General, &rango).is_err());
+    assert!(cliente.try_exportar_datos(&1, &formato, &hash, &1).is_err());
+
+    env.mock_all_auths();
+    cliente.realizar_respaldo(&1, &ubicacion, &autor);
+    firmo_solo_admin(&env, &admin, &contract_id, "realizar_respaldo", (1, ubicacion.clone(), autor.clone()).into_val(&env));
+    cliente.generar_informe(&1, &TipoInforme::General, &rango);
+    firmo_solo_admin(&env, &admin, &contract_id, "generar_informe", (1, TipoInforme::General, rango.clone()).into_val(&env));
+    cliente.exportar_datos(&1, &formato, &hash, &1);
+    firmo_solo_admin(&env, &admin, &contract_id, "exportar_datos", (1, formato.clone(), hash.clone(), 1_u32).into_val(&env));
+
+    // El perfil del administrador no se puede desactivar ni borrar
+    assert!(cliente.try_desactivar_usuario(&admin).is_err());
+    assert!(cliente.try_eliminar_usuario(&admin).is_err());
+    assert!(cliente.obtener_usuario(&admin).unwrap().activo);
+}
+
+#[test]
+fn test_cambio_de_token_con_saldos() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let cliente = DocumentosContractClient::new(&env, &contract_id);
+    let tesoreria = Address::generate(&env);
+    let anterior = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let nuevo = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let empleador = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &anterior).mint(&empleador, &100);
+
+    cliente.configurar_tarifas(&Tarifas { token: anterior.clone(), tesoreria: tesoreria.clone(), emision: 0, verificacion: 10 });
+    cliente.depositar_creditos(&empleador, &2);
+    let cambio = Tarifas { token: nuevo.clone(), tesoreria: tesoreria.clone(), emision: 0, verificacion: 10 };
+
+    // Con créditos sin gastar no se cambia de token
+    assert!(cliente.try_configurar_tarifas(&cambio).is_err());
+    cliente.configurar_tarifas(&Tarifas { token: anterior.clone(), tesoreria: tesoreria.clone(), emision: 0, verificacion: 5 });
+    env.as_contract(&contract_id, || {
+        let mut creditos: Map<Address, u32> = Map::new(&env);
+        creditos.set(empleador.clone(), 0);
+        env.storage().persistent().set(&CREDITOS_KEY, &creditos);
+    });
+
+    // Ni con fondos sin retirar: primero se retiran
+    assert!(cliente.try_configurar_tarifas(&cambio).is_err());
+    cliente.retirar_fondos(&20);
+    cliente.configurar_tarifas(&cambio);
+    assert_eq!(cliente.obtener_tarifas(), Some(cambio));
+    assert_eq!(token::Client::new(&env, &anterior).balance(&tesoreria), 20);
+}
+
+#[test]
+#[should_panic(expected = "Fondos insuficientes")]
+fn test_retirar_fondos_insuficientes() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let activo = env.register_stellar_asset_contract_v2(Address::generate(&env));
+
+    let tarifas = Tarifas { token: activo.address(), tesoreria: Address::generate(&env), emision: 0, verificacion: 5 };
+    env.as_contract(&contract_id, || {
+        DocumentosContract::configurar_tarifas(env.clone(), tarifas);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::retirar_fondos(env.clone(), 1);
+    });
+}
+
+#[test]
+fn test_creditos_de_verificacion() {
+    let env = Env::default();
+    let contract_id = registro_con_admin(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+
+    let activo = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let token = token::Client::new(&env, &activo.address());
+    let empleador = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &activo.address()).mint(&empleador, &1_000);
+
+    let tarifas = Tarifas { token: activo.address(), tesoreria: Address::generate(&env), emision: 0, verificacion: 10 };
+    env.as_contract(&contract_id, || {
+        DocumentosContract::configurar_tarifas(env.clone(), tarifas);
+        DocumentosContract::registrar_documento(env.clone(), 1, registrador.clone(), String::from_str(&env, "Diploma"), String::from_str(&env, "Activo"), 1640995200);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::depositar_creditos(env.clone(), empleador.clone(), 3);
+    });
+    assert_eq!(token.balance(&empleador), 970);
+    let saldo = env.as_contract(&contract_id, || DocumentosContract::saldo_creditos(env.clone(), empleador.clone()));
+    assert_eq!(saldo, 3);
+
+    // Cada verificación consume un crédito y no cobra la tarifa
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 1, empleador.clone(), None);
+    });
+    // Un documento que el contrato no tiene devuelve el crédito
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 99, empleador.clone(), None);
+    });
+    let saldo = env.as_contract(&contract_id, || DocumentosContract::saldo_creditos(env.clone(), empleador.clone()));
+    assert_eq!(saldo, 2);
+    assert_eq!(token.balance(&empleador), 970);
+
+    // Sin créditos se vuelve a cobrar la tarifa
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 1, empleador.clone(), None);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 1, empleador.clone(), None);
+    });
+    env.as_contract(&contract_id, || {
+        DocumentosContract::registrar_verificacion(env.clone(), 1, empleador.clone(), None);
+    });
+    let saldo = env.as_contract(&contract_id, || DocumentosContract::saldo_creditos(env.clone(), empleador.clone()));
+    assert_eq!(saldo, 0);
+    assert_eq!(token.balance(&empleador), 960);
+}
+
+#[test]
+fn test_certificado_intransferible() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let contract_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &contract_id, "Registrador");
+    let client = DocumentosContractClient::new(&env, &contract_id);
+
+    let emisor = alta_usuario(&env, &contract_id, "Emisor");
+    let titular = Address::generate(&env);
+    let otro = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let titulo = String::from_str(&env, "Licenciatura en Física");
+    client.registrar_documento(&1, &registrador, &titulo, &String::from_str(&env, "Activo"), &1640995200);
+    client.registrar_emision(&1, &emisor, &None, &hash, &hash, &None);
+
+    assert_eq!(client.balance(&titular), 0);
+    client.vincular_titular(&1, &titular);
+    assert_eq!(client.balance(&titular), 1);
+    assert_eq!(client.name(), String::from_str(&env, "Certificado CertiWeb"));
+    assert_eq!(client.symbol(), String::from_str(&env, "CERTI"));
+    assert_eq!(client.decimals(), 0);
+    assert_eq!(
+        client.metadatos_certificado(&1),
+        Some(MetadatosCertificado { id_documento: 1, titular: titular.clone(), titulo, fecha_emision_academica: 1640995200 })
+    );
+
+    // Ninguna operación de transferencia está permitida
+    assert!(client.try_transfer(&titular, &otro, &1).is_err());
+    assert!(client.try_transfer_from(&otro, &titular, &otro, &1).is_err());
+    assert!(client.try_approve(&titular, &otro, &1, &100).is_err());
+    assert!(client.try_burn(&titular, &1).is_err());
+    assert_eq!(client.balance(&titular), 1);
+    assert_eq!(client.allowance(&titular, &otro), 0);
+
+    // La revocación quema el token
+    client.actualizar_estado(&1, &emisor, &String::from_str(&env, "Revocado"));
+    assert_eq!(client.balance(&titular), 0);
+    assert_eq!(client.metadatos_certificado(&1), None);
+    assert!(client.certificados_de(&titular).is_empty());
+}
+
+/// Contrato de ejemplo que usa la interfaz `Verificador` desde otro contrato.
+mod beca {
+    use crate::VerificadorClient;
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    #[contract]
+    pub struct BecaContract;
+
+    #[contractimpl]
+    impl BecaContract {
+        /// Acepta al solicitante si tiene un título válido del tipo exigido.
+        pub fn solicitar(env: Env, registro: Address, solicitante: Address, id_tipo: i32) -> bool {
+            VerificadorClient::new(&env, &registro).titular_tiene_credencial(&solicitante, &id_tipo)
+        }
+
+        pub fn documento_valido(env: Env, registro: Address, id_documento: i32) -> bool {
+            VerificadorClient::new(&env, &registro).es_valido(&id_documento)
+        }
+    }
+}
+
+#[test]
+fn test_interfaz_entre_contratos() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let registro_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &registro_id, "Registrador");
+    let registro = DocumentosContractClient::new(&env, &registro_id);
+    let beca = beca::BecaContractClient::new(&env, &env.register(beca::BecaContract, ()));
+
+    let emisor = alta_usuario(&env, &registro_id, "Emisor");
+    let graduado = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    registro.registrar_tipo(
+        &1,
+        &TipoDocumento {
+            nombre: String::from_str(&env, "Título de grado"),
+            campos_requeridos: vec![&env],
+            vigencia: None,
+            politica_firma: PoliticaFirma::Emisor,
+        },
+    );
+    registro.registrar_documento_tipado(
+        &10, &registrador,
+        &1,
+        &String::from_str(&env, "Ingeniería Química"),
+        &String::from_str(&env, "Activo"),
+        &1640995200,
+        &map![&env],
+    );
+    registro.registrar_emision(&10, &emisor, &None, &hash, &hash, &None);
+
+    assert!(beca.documento_valido(&registro_id, &10));
+    assert!(!beca.documento_valido(&registro_id, &99));
+    // Sin titular vinculado todavía no hay credencial
+    assert!(!beca.solicitar(&registro_id, &graduado, &1));
+
+    registro.vincular_titular(&10, &graduado);
+    assert!(beca.solicitar(&registro_id, &graduado, &1));
+    assert!(!beca.solicitar(&registro_id, &graduado, &2));
+    assert!(!beca.solicitar(&registro_id, &Address::generate(&env), &1));
+
+    registro.actualizar_estado(&10, &emisor, &String::from_str(&env, "Revocado"));
+    assert!(!beca.documento_valido(&registro_id, &10));
+    assert!(!beca.solicitar(&registro_id, &graduado, &1));
+}
+
+#[test]
+fn test_interfaz_rechaza_credenciales_sin_emisor_autorizado() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let registro_id = desplegar(&env);
+    let registrador = alta_usuario(&env, &registro_id, "Registrador");
+    let registro = DocumentosContractClient::new(&env, &registro_id);
+    let beca = beca::BecaContractClient::new(&env, &env.register(beca::BecaContract, ()));
+
+    let emisor = alta_usuario(&env, &registro_id, "Emisor");
+    let graduado = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let activo = String::from_str(&env, "Activo");
+    registro.registrar_tipo(
+        &1,
+        &TipoDocumento {
+            nombre: String::from_str(&env, "Título de grado"),
+            campos_requeridos: vec![&env],
+            vigencia: None,
+            politica_firma: PoliticaFirma::Ninguna,
+        },
+    );
+    // Registrado pero sin firma de ningún emisor
+    registro.registrar_documento_tipado(&10, &registrador, &1, &String::from_str(&env, "Ingeniería"), &activo, &1640995200, &map![&env]);
+    assert!(registro.verificar_documento_publico(&10, &None).valido);
+    assert!(!beca.documento_valido(&registro_id, &10));
+
+    // Una dirección sin rol de emisión no puede firmarse su propio título
+    let autoemisor = Address::generate(&env);
+    registro.registrar_documento_tipado(&11, &registrador, &1, &String::from_str(&env, "Medicina"), &activo, &1640995200, &map![&env]);
+    assert!(registro.try_registrar_emision(&11, &autoemisor, &None, &hash, &hash, &None).is_err());
+    assert!(!beca.documento_valido(&registro_id, &11));
+
+    // El rol se comprueba al emitir: perderlo después no invalida lo firmado
+    registro.registrar_emision(&11, &emisor, &None, &hash, &hash, &None);
+    registro.vincular_titular(&11, &graduado);
+    assert!(beca.solicitar(&registro_id, &graduado, &1));
+    registro.revocar_rol(&emisor, &String::from_str(&env, "Emisor"));
+    assert!(registro.verificar_documento_publico(&11, &None).valido);
+    assert!(beca.documento_valido(&registro_id, &11));
+    assert!(beca.solicitar(&registro_id, &graduado, &1));
+    // pero ya no puede firmar nada nuevo
+    registro.registrar_documento_tipado(&12, &registrador, &1, &String::from_str(&env, "Derecho"), &activo, &1640995200, &map![&env]);
+    assert!(registro.try_registrar_emision(&12, &emisor, &None, &hash, &hash, &None).is_err());
+    assert!(!beca.documento_valido(&registro_id, &12));
+
+    // Sólo una baja explícita del emisor invalida lo ya emitido
+    registro.desactivar_emisor(&emisor);
+    assert_eq!(
+        registro.verificar_documento_publico(&11, &None).motivos,
+        vec![&env, MotivoRechazo::EmisorInactivo]
+    );
+    assert!(!beca.documento_valido(&registro_id, &11));
+    assert!(!beca.solicitar(&registro_id, &graduado, &1));
+}
+
+#[test]
+fn test_verificar_externo() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let propio_id = desplegar(&env);
+    let propio = DocumentosContractClient::new(&env, &propio_id);
+    let socio_id = desplegar(&env);
+    let socio = DocumentosContractClient::new(&env, &socio_id);
+
+    let emisor = alta_usuario(&env, &socio_id, "Emisor");
+    let registrador = alta_usuario(&env, &propio_id, "Registrador");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let activo = String::from_str(&env, "Activo");
+    socio.registrar_documento(&7, &emisor, &String::from_str(&env, "Maestría en Economía"), &activo, &1640995200);
+    socio.registrar_emision(&7, &emisor, &None, &hash, &hash, &None);
+    propio.registrar_documento(&1, &registrador, &String::from_str(&env, "Diploma"), &activo, &1640995200);
+
+    assert!(propio.try_verificar_externo(&socio_id, &7).is_err());
+
+    propio.agregar_par(&socio_id);
+    assert_eq!(propio.pares_confiables(), vec![&env, socio_id.clone()]);
+    // Al par sólo se le pregunta por la interfaz estable
+    let externo = propio.verificar_externo(&socio_id, &7);
+    assert!(externo.valido);
+    assert!(externo.motivos.is_empty());
+    assert_eq!(externo.emisor, None);
+    assert!(socio.es_valido(&7));
+    let rechazado = propio.verificar_externo(&socio_id, &8);
+    assert!(!rechazado.valido);
+    assert_eq!(rechazado.motivos, vec![&env, MotivoRechazo::RechazadoPorRegistro]);
+    // El propio registro se verifica sin figurar en la lista
+    assert_eq!(propio.verificar_externo(&propio_id, &1), propio.verificar_documento_publico(&1, &None));
+
+    // Un par que no responde como registro da un resultado inválido
+    let ajeno_id = env.register(beca::BecaContract, ());
+    propio.agregar_par(&ajeno_id);
+    let ajeno = propio.verificar_externo(&ajeno_id, &7);
+    assert!(!ajeno.valido);
+    assert_eq!(ajeno.motivos, vec![&env, MotivoRechazo::RegistroNoDisponible]);
+    assert_eq!(ajeno.emisor, None);
+
+    propio.quitar_par(&socio_id);
+    assert!(propio.try_verificar_externo(&socio_id, &7).is_err());
+}
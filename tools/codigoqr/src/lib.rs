@@ -0,0 +1,142 @@
+//! Códigos QR de verificación para documentos impresos.
+//!
+//! El código contiene un URI compacto firmado por el emisor:
+//!
+//! ```text
+//! <base>?r=<red>&c=<contrato C...>&d=<id>&e=<emisor G...>&f=<firma>
+//! ```
+//!
+//! `f` es la firma ed25519 (base64url sin relleno) de
+//! `certiweb:v1:<red>:<contrato>:<id>` con la clave del emisor. Quien escanea
+//! el código comprueba la firma con [`leer_uri`] y después consulta
+//! `verificar_documento_publico` con [`consultar`], que además exige que el
+//! emisor del código sea el registrado en el contrato.
+
+use std::fmt;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use certificado::{DocumentosContractClient, ResultadoVerificacion};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+pub mod qr;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug)]
+pub enum Error {
+    /// El URI no tiene la forma esperada.
+    Formato(String),
+    /// La firma no corresponde al emisor del código.
+    Firma,
+    /// El código pertenece a otro contrato.
+    OtroContrato(String),
+    /// El emisor del código no es el registrado para el documento.
+    Emisor,
+    /// No se pudo generar la imagen del código.
+    Imagen(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Formato(e) => write!(f, "código inválido: {e}"),
+            Error::Firma => write!(f, "la firma del código no es válida"),
+            Error::OtroContrato(c) => write!(f, "el código es de otro contrato: {c}"),
+            Error::Emisor => write!(f, "el emisor del código no es el del documento"),
+            Error::Imagen(e) => write!(f, "no se pudo generar el código QR: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Lo que identifica un documento en un código de verificación.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Carga {
+    /// Red Stellar del contrato (`testnet`, `public`, ...).
+    pub red: String,
+    /// ID del contrato (strkey `C...`).
+    pub contrato: String,
+    pub id_documento: i32,
+    /// Cuenta del emisor (strkey `G...`).
+    pub emisor: String,
+}
+
+impl Carga {
+    /// Mensaje que firma el emisor.
+    pub fn mensaje(&self) -> String {
+        format!("certiweb:v1:{}:{}:{}", self.red, self.contrato, self.id_documento)
+    }
+}
+
+/// Construye el URI firmado de un documento. `base` es la página de
+/// verificación (p. ej. `https://certiweb.example/v`).
+pub fn uri(base: &str, red: &str, contrato: &str, id_documento: i32, clave: &SigningKey) -> String {
+    let carga = Carga {
+        red: red.to_string(),
+        contrato: contrato.to_string(),
+        id_documento,
+        emisor: stellar_strkey::ed25519::PublicKey(clave.verifying_key().to_bytes()).to_string(),
+    };
+    let firma = URL_SAFE_NO_PAD.encode(clave.sign(carga.mensaje().as_bytes()).to_bytes());
+    format!(
+        "{base}?r={}&c={}&d={}&e={}&f={firma}",
+        carga.red, carga.contrato, carga.id_documento, carga.emisor
+    )
+}
+
+/// Lee un URI de verificación y comprueba la firma del emisor.
+pub fn leer_uri(uri: &str) -> Result<Carga, Error> {
+    let Some((_, consulta)) = uri.split_once('?') else {
+        return Err(Error::Formato("falta la consulta".into()));
+    };
+    let parametro = |nombre: &str| {
+        consulta
+            .split('&')
+            .find_map(|par| par.strip_prefix(nombre)?.strip_prefix('='))
+            .ok_or_else(|| Error::Formato(format!("falta el parámetro {nombre}")))
+    };
+
+    let carga = Carga {
+        red: parametro("r")?.to_string(),
+        contrato: parametro("c")?.to_string(),
+        id_documento: parametro("d")?
+            .parse()
+            .map_err(|_| Error::Formato("ID de documento inválido".into()))?,
+        emisor: parametro("e")?.to_string(),
+    };
+
+    let clave = stellar_strkey::ed25519::PublicKey::from_string(&carga.emisor)
+        .map_err(|_| Error::Formato("emisor inválido".into()))?;
+    let clave = VerifyingKey::from_bytes(&clave.0).map_err(|_| Error::Firma)?;
+    let firma: [u8; 64] = URL_SAFE_NO_PAD
+        .decode(parametro("f")?)
+        .ok()
+        .and_then(|firma| firma.try_into().ok())
+        .ok_or_else(|| Error::Formato("firma inválida".into()))?;
+    clave
+        .verify(carga.mensaje().as_bytes(), &Signature::from_bytes(&firma))
+        .map_err(|_| Error::Firma)?;
+    Ok(carga)
+}
+
+/// Consulta el documento del código en el contrato. Falla si el código es de
+/// otro contrato o si su emisor no es el registrado para el documento; si el
+/// documento no existe devuelve el resultado (no válido) tal cual.
+pub fn consultar(
+    registro: &DocumentosContractClient<'_>,
+    carga: &Carga,
+) -> Result<ResultadoVerificacion, Error> {
+    let contrato = registro.address.to_string().to_string();
+    if carga.contrato != contrato {
+        return Err(Error::OtroContrato(carga.contrato.clone()));
+    }
+    let resultado = registro.verificar_documento_publico(&carga.id_documento, &None);
+    match &resultado.emisor {
+        Some(emisor) if emisor.to_string().to_string() == carga.emisor => Ok(resultado),
+        None if resultado.estado.is_none() => Ok(resultado),
+        _ => Err(Error::Emisor),
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use codigoqr::{qr, uri};
+use ed25519_dalek::SigningKey;
+
+const USO: &str = "uso: codigoqr <contrato C...> <id_documento> [--archivo-clave <archivo>] [--red testnet|public] \
+[--base <url>] [--formato svg|png] [--escala <px, 1-32>] [--salida <archivo>]\n\
+la semilla secreta S... del emisor se lee de --archivo-clave o de la variable CODIGOQR_CLAVE";
+
+/// Variable de entorno con la semilla secreta del emisor. Nunca se acepta como
+/// argumento, para que no quede en el historial ni en la lista de procesos.
+const VARIABLE_CLAVE: &str = "CODIGOQR_CLAVE";
+
+fn main() -> ExitCode {
+    let mut posicionales = Vec::new();
+    let mut archivo_clave: Option<PathBuf> = None;
+    let mut red = String::from("testnet");
+    let mut base = String::from("https://certiweb.example/v");
+    let mut formato = String::from("svg");
+    let mut escala = 8;
+    let mut salida: Option<PathBuf> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            posicionales.push(arg);
+            continue;
+        }
+        let Some(valor) = args.next() else {
+            eprintln!("{USO}");
+            return ExitCode::FAILURE;
+        };
+        match arg.as_str() {
+            "--archivo-clave" => archivo_clave = Some(PathBuf::from(valor)),
+            "--red" => red = valor,
+            "--base" => base = valor,
+            "--formato" if valor == "svg" || valor == "png" => formato = valor,
+            "--escala" => match valor.parse() {
+                Ok(px) => escala = px,
+                Err(_) => {
+                    eprintln!("{USO}");
+                    return ExitCode::FAILURE;
+                }
+            },
+            "--salida" => salida = Some(PathBuf::from(valor)),
+            _ => {
+                eprintln!("{USO}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let [contrato, id] = posicionales.as_slice() else {
+        eprintln!("{USO}");
+        return ExitCode::FAILURE;
+    };
+    let clave = match archivo_clave {
+        Some(ruta) => match std::fs::read_to_string(&ruta) {
+            Ok(texto) => texto.trim().to_string(),
+            Err(e) => {
+                eprintln!("error: no se pudo leer {}: {e}", ruta.display());
+                return ExitCode::FAILURE;
+            }
+        },
+        None => match std::env::var(VARIABLE_CLAVE) {
+            Ok(clave) => clave,
+            Err(_) => {
+                eprintln!("{USO}");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let Ok(id) = id.parse() else {
+        eprintln!("{USO}");
+        return ExitCode::FAILURE;
+    };
+    let clave = match stellar_strkey::ed25519::PrivateKey::from_string(&clave) {
+        Ok(clave) => SigningKey::from_bytes(&clave.0),
+        Err(_) => {
+            eprintln!("error: la clave debe ser una semilla secreta S...");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let enlace = uri(&base, &red, contrato, id, &clave);
+    let imagen = match formato.as_str() {
+        "png" => qr::png(&enlace, escala),
+        _ => qr::svg(&enlace).map(String::into_bytes),
+    };
+    let imagen = match imagen {
+        Ok(imagen) => imagen,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let salida = salida.unwrap_or_else(|| PathBuf::from(format!("documento-{id}.{formato}")));
+    if let Err(e) = std::fs::write(&salida, imagen) {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+    println!("{enlace}");
+    println!("código QR escrito en {}", salida.display());
+    ExitCode::SUCCESS
+}
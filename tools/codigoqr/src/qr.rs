@@ -0,0 +1,65 @@
+//! Imagen del código QR en SVG o PNG, con el margen de 4 módulos que pide la
+//! norma.
+
+use png::{BitDepth, ColorType, Encoder};
+use qrcodegen::{QrCode, QrCodeEcc};
+
+use crate::Error;
+
+const MARGEN: i32 = 4;
+/// Píxeles por módulo como máximo: el código más grande (177 módulos más el
+/// margen) queda en unos 6000 × 6000 píxeles.
+pub const MAX_ESCALA: u32 = 32;
+
+fn codificar(texto: &str) -> Result<QrCode, Error> {
+    QrCode::encode_text(texto, QrCodeEcc::Medium).map_err(|e| Error::Imagen(e.to_string()))
+}
+
+/// SVG con un módulo por unidad; el visor lo escala sin perder nitidez.
+pub fn svg(texto: &str) -> Result<String, Error> {
+    let codigo = codificar(texto)?;
+    let lado = codigo.size() + 2 * MARGEN;
+    let mut trazo = String::new();
+    for y in 0..codigo.size() {
+        for x in 0..codigo.size() {
+            if codigo.get_module(x, y) {
+                trazo.push_str(&format!("M{},{}h1v1h-1z", x + MARGEN, y + MARGEN));
+            }
+        }
+    }
+    Ok(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {lado} {lado}\" shape-rendering=\"crispEdges\">\
+<rect width=\"100%\" height=\"100%\" fill=\"#fff\"/><path d=\"{trazo}\" fill=\"#000\"/></svg>\n"
+    ))
+}
+
+/// PNG en escala de grises con `escala` píxeles por módulo (de 1 a
+/// [`MAX_ESCALA`]).
+pub fn png(texto: &str, escala: u32) -> Result<Vec<u8>, Error> {
+    if escala > MAX_ESCALA {
+        return Err(Error::Imagen(format!("la escala no puede superar {MAX_ESCALA} píxeles por módulo")));
+    }
+    let codigo = codificar(texto)?;
+    let escala = escala.max(1);
+    let lado = (codigo.size() + 2 * MARGEN) as u32 * escala;
+
+    let mut pixeles = Vec::with_capacity(lado as usize * lado as usize);
+    for fila in 0..lado {
+        for columna in 0..lado {
+            let x = (columna / escala) as i32 - MARGEN;
+            let y = (fila / escala) as i32 - MARGEN;
+            // get_module devuelve false fuera del código, es decir, el margen
+            pixeles.push(if codigo.get_module(x, y) { 0 } else { 255 });
+        }
+    }
+
+    let mut salida = Vec::new();
+    let mut encoder = Encoder::new(&mut salida, lado, lado);
+    encoder.set_color(ColorType::Grayscale);
+    encoder.set_depth(BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut escritor| escritor.write_image_data(&pixeles))
+        .map_err(|e| Error::Imagen(e.to_string()))?;
+    Ok(salida)
+}
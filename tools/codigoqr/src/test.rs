@@ -0,0 +1,106 @@
+#![cfg(test)]
+
+use super::*;
+use certificado::DocumentosContract;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    Address, BytesN, Env, String as SorobanString,
+};
+
+const BASE: &str = "https://certiweb.example/v";
+
+fn clave_emisor() -> SigningKey {
+    SigningKey::from_bytes(&[42; 32])
+}
+
+fn registro_con_emision<'a>(env: &'a Env, clave: &SigningKey) -> DocumentosContractClient<'a> {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let admin = Address::generate(env);
+    let contract_id = env.register(DocumentosContract, (admin.clone(),));
+    let cliente = DocumentosContractClient::new(env, &contract_id);
+    cliente.registrar_documento(
+        &1,
+        &admin,
+        &SorobanString::from_str(env, "Ingeniería en Sistemas"),
+        &SorobanString::from_str(env, "Activo"),
+        &1640995200,
+    );
+    let emisor = stellar_strkey::ed25519::PublicKey(clave.verifying_key().to_bytes()).to_string();
+    let emisor = Address::from_str(env, &emisor);
+    cliente.crear_usuario(&emisor, &None, &None, &SorobanString::from_str(env, "Emisor"));
+    cliente.registrar_emision(
+        &1,
+        &emisor,
+        &None,
+        &BytesN::from_array(env, &[3; 32]),
+        &BytesN::from_array(env, &[7; 32]),
+        &None,
+    );
+    cliente
+}
+
+#[test]
+fn test_uri_firmado() {
+    let clave = clave_emisor();
+    let contrato = stellar_strkey::Contract([0; 32]).to_string();
+    let enlace = uri(BASE, "testnet", &contrato, 12, &clave);
+
+    let carga = leer_uri(&enlace).unwrap();
+    assert_eq!(carga.red, "testnet");
+    assert_eq!(carga.contrato, contrato);
+    assert_eq!(carga.id_documento, 12);
+    assert!(carga.emisor.starts_with('G'));
+
+    // Cambiar el documento invalida la firma
+    let alterado = enlace.replace("&d=12&", "&d=13&");
+    assert!(matches!(leer_uri(&alterado), Err(Error::Firma)));
+    assert!(matches!(leer_uri(BASE), Err(Error::Formato(_))));
+}
+
+#[test]
+fn test_consultar() {
+    let env = Env::default();
+    let clave = clave_emisor();
+    let registro = registro_con_emision(&env, &clave);
+    let contrato = registro.address.to_string().to_string();
+
+    let carga = leer_uri(&uri(BASE, "testnet", &contrato, 1, &clave)).unwrap();
+    let resultado = consultar(&registro, &carga).unwrap();
+    assert!(resultado.valido);
+    assert_eq!(resultado.hash_titular.unwrap().len(), 32);
+
+    // Un código firmado por otra clave no es del emisor del documento
+    let ajena = SigningKey::from_bytes(&[1; 32]);
+    let carga = leer_uri(&uri(BASE, "testnet", &contrato, 1, &ajena)).unwrap();
+    assert!(matches!(consultar(&registro, &carga), Err(Error::Emisor)));
+
+    // Un documento inexistente se informa como no válido
+    let carga = leer_uri(&uri(BASE, "testnet", &contrato, 99, &clave)).unwrap();
+    assert!(!consultar(&registro, &carga).unwrap().valido);
+
+    let otro = registro_con_emision(&env, &clave);
+    let carga = leer_uri(&uri(BASE, "testnet", &contrato, 1, &clave)).unwrap();
+    assert!(matches!(consultar(&otro, &carga), Err(Error::OtroContrato(_))));
+}
+
+#[test]
+fn test_imagen_qr() {
+    let enlace = uri(BASE, "testnet", "C", 1, &clave_emisor());
+
+    let svg = qr::svg(&enlace).unwrap();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains("<path d=\"M"));
+
+    let png = qr::png(&enlace, 4).unwrap();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    // Ancho en la cabecera IHDR: (módulos + margen) * escala
+    let ancho = u32::from_be_bytes(png[16..20].try_into().unwrap());
+    assert_eq!(ancho % 4, 0);
+    assert!(ancho > (21 + 8) * 4);
+
+    // Una escala desmesurada es un error, no un desbordamiento
+    assert!(qr::png(&enlace, qr::MAX_ESCALA).is_ok());
+    assert!(matches!(qr::png(&enlace, qr::MAX_ESCALA + 1), Err(Error::Imagen(_))));
+    assert!(matches!(qr::png(&enlace, u32::MAX), Err(Error::Imagen(_))));
+}
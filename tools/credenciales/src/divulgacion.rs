@@ -0,0 +1,155 @@
+//! Divulgación selectiva de campos.
+//!
+//! El emisor guarda en el contrato, por cada campo del documento, el
+//! compromiso `sha256(sal || valor)` (`registrar_compromisos`) y entrega al
+//! titular los valores con sus sales. El titular comparte sólo los campos que
+//! quiere en un paquete (p. ej. la carrera pero no el promedio) y el
+//! verificador comprueba cada uno con `verificar_campo`, además de que el
+//! documento siga vigente.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use certificado::MotivoRechazo;
+
+use crate::{nombre_campo_valido, RegistroDocumentos};
+
+/// Un campo del documento con la sal de su compromiso.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Campo {
+    pub nombre: String,
+    pub valor: String,
+    pub sal: [u8; 32],
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// El paquete no tiene la forma esperada.
+    Formato(String),
+    /// El paquete pertenece a otro registro.
+    OtroContrato(String),
+    /// El contrato no da el documento por válido (revocado, vencido, no
+    /// encontrado...).
+    Rechazado(Vec<MotivoRechazo>),
+    /// El valor o la sal del campo no corresponden al compromiso.
+    CampoNoCoincide(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Formato(e) => write!(f, "paquete inválido: {e}"),
+            Error::OtroContrato(c) => write!(f, "el paquete es de otro contrato: {c}"),
+            Error::Rechazado(motivos) => write!(f, "el documento no es válido: {motivos:?}"),
+            Error::CampoNoCoincide(c) => write!(f, "el campo {c} no coincide con su compromiso"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// `sha256(sal || valor)`, el mismo cálculo que hace `verificar_campo`.
+pub fn compromiso(valor: &str, sal: &[u8; 32]) -> [u8; 32] {
+    let mut hash = Sha256::new();
+    hash.update(sal);
+    hash.update(valor.as_bytes());
+    hash.finalize().into()
+}
+
+/// Asigna a cada campo `(nombre, valor)` una sal aleatoria nueva.
+pub fn preparar(campos: &[(&str, &str)]) -> Result<Vec<Campo>, getrandom::Error> {
+    campos
+        .iter()
+        .map(|(nombre, valor)| {
+            let mut sal = [0; 32];
+            getrandom::getrandom(&mut sal)?;
+            Ok(Campo {
+                nombre: nombre.to_string(),
+                valor: valor.to_string(),
+                sal,
+            })
+        })
+        .collect()
+}
+
+/// Compromisos por nombre de campo, listos para `registrar_compromisos`.
+pub fn compromisos(campos: &[Campo]) -> BTreeMap<String, [u8; 32]> {
+    campos
+        .iter()
+        .map(|campo| (campo.nombre.clone(), compromiso(&campo.valor, &campo.sal)))
+        .collect()
+}
+
+/// Paquete con sólo los campos de `divulgar`, para entregar a un verificador.
+pub fn paquete(contrato: &str, id_documento: i32, campos: &[Campo], divulgar: &[&str]) -> Value {
+    let divulgados: Vec<Value> = campos
+        .iter()
+        .filter(|campo| divulgar.contains(&campo.nombre.as_str()))
+        .map(|campo| {
+            json!({
+                "nombre": campo.nombre,
+                "valor": campo.valor,
+                "sal": hex::encode(campo.sal),
+            })
+        })
+        .collect();
+    json!({
+        "contrato": contrato,
+        "idDocumento": id_documento,
+        "campos": divulgados,
+    })
+}
+
+/// Comprueba que el documento siga siendo válido y cada campo del paquete
+/// contra el contrato, y devuelve los campos divulgados como `nombre -> valor`.
+/// Un paquete sin campos no prueba nada y se rechaza.
+pub fn verificar_paquete(
+    paquete: &Value,
+    registro: &impl RegistroDocumentos,
+) -> Result<BTreeMap<String, String>, Error> {
+    let Some(contrato) = paquete["contrato"].as_str() else {
+        return Err(Error::Formato("falta contrato".into()));
+    };
+    if contrato != registro.contrato() {
+        return Err(Error::OtroContrato(contrato.to_string()));
+    }
+    let Some(id) = paquete["idDocumento"].as_i64().and_then(|id| i32::try_from(id).ok()) else {
+        return Err(Error::Formato("falta idDocumento".into()));
+    };
+    let Some(campos) = paquete["campos"].as_array() else {
+        return Err(Error::Formato("falta campos".into()));
+    };
+    if campos.is_empty() {
+        return Err(Error::Formato("el paquete no divulga ningún campo".into()));
+    }
+    let motivos = registro.motivos_rechazo(id);
+    if !motivos.is_empty() {
+        return Err(Error::Rechazado(motivos));
+    }
+
+    let mut divulgados = BTreeMap::new();
+    for campo in campos {
+        let (Some(nombre), Some(valor), Some(sal)) = (
+            campo["nombre"].as_str(),
+            campo["valor"].as_str(),
+            campo["sal"].as_str(),
+        ) else {
+            return Err(Error::Formato("campo incompleto".into()));
+        };
+        if !nombre_campo_valido(nombre) {
+            return Err(Error::Formato(format!("nombre de campo inválido: {nombre}")));
+        }
+        let sal: [u8; 32] = hex::decode(sal)
+            .ok()
+            .and_then(|sal| sal.try_into().ok())
+            .ok_or_else(|| Error::Formato(format!("sal inválida en {nombre}")))?;
+        if !registro.verificar_campo(id, nombre, valor, &sal) {
+            return Err(Error::CampoNoCoincide(nombre.to_string()));
+        }
+        divulgados.insert(nombre.to_string(), valor.to_string());
+    }
+    Ok(divulgados)
+}
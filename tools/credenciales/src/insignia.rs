@@ -0,0 +1,118 @@
+//! Open Badges 3.0 (`OpenBadgeCredential`).
+//!
+//! La insignia es una credencial W3C con el mismo `credentialStatus` que
+//! [`crate::vc`]; el logro, los criterios y el hash de la imagen se leen de
+//! `obtener_insignia` y [`verificar`] los compara con el contrato.
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::vc::{documento_vigente, emisor, estado_credencial, Opciones, ResultadoVc};
+use crate::{fecha_iso, Documento, Insignia, RegistroDocumentos};
+
+/// Contexto JSON-LD de Open Badges 3.0.
+pub const CONTEXTO_OB3: &str = "https://purl.imsglobal.org/spec/ob/v3p0/context-3.0.3.json";
+
+/// Construye la insignia de un documento y sus datos de logro.
+///
+/// `url_imagen` es la ubicación pública de la imagen cuyo hash guarda el
+/// contrato.
+pub fn credencial(
+    documento: &Documento,
+    insignia: &Insignia,
+    contrato: &str,
+    url_imagen: &str,
+    opciones: &Opciones,
+) -> Value {
+    let mut emisor = emisor(contrato, opciones);
+    emisor["type"] = json!(["Profile"]);
+
+    let mut sujeto = json!({
+        "type": ["AchievementSubject"],
+        "achievement": {
+            "id": format!("urn:certiweb:{contrato}:{}#logro", documento.id),
+            "type": ["Achievement"],
+            "name": insignia.logro,
+            "description": documento.titulo,
+            "criteria": { "narrative": insignia.criterios },
+            "image": {
+                "id": url_imagen,
+                "type": "Image",
+                "hashImagen": hex::encode(insignia.hash_imagen),
+            },
+        },
+    });
+    if let Some(titular) = &opciones.titular {
+        sujeto["id"] = json!(titular);
+    }
+
+    json!({
+        "@context": [
+            "https://www.w3.org/ns/credentials/v2",
+            CONTEXTO_OB3,
+            { "@vocab": "https://certiweb.example/vocab#" }
+        ],
+        "id": format!("urn:certiweb:{contrato}:{}", documento.id),
+        "type": ["VerifiableCredential", "OpenBadgeCredential"],
+        "name": insignia.logro,
+        "issuer": emisor,
+        "validFrom": fecha_iso(documento.fecha_emision_academica),
+        "credentialSubject": sujeto,
+        "credentialStatus": estado_credencial(documento.id, contrato, opciones),
+    })
+}
+
+/// Lee el documento y su insignia del registro y construye la credencial.
+/// Devuelve `None` si el documento no existe o no es una insignia.
+pub fn exportar(
+    registro: &impl RegistroDocumentos,
+    id: i32,
+    url_imagen: &str,
+    opciones: &Opciones,
+) -> Option<Value> {
+    let documento = registro.obtener_documento(id)?;
+    let insignia = registro.obtener_insignia(id)?;
+    Some(credencial(
+        &documento,
+        &insignia,
+        &registro.contrato(),
+        url_imagen,
+        opciones,
+    ))
+}
+
+/// Comprueba una insignia contra el estado actual del contrato. Si se pasan
+/// los bytes de la imagen, también se comprueba su hash.
+pub fn verificar(
+    ob: &Value,
+    registro: &impl RegistroDocumentos,
+    imagen: Option<&[u8]>,
+) -> ResultadoVc {
+    let documento = match documento_vigente(ob, registro) {
+        Ok(documento) => documento,
+        Err(resultado) => return resultado,
+    };
+    let Some(insignia) = registro.obtener_insignia(documento.id) else {
+        return ResultadoVc::NoEncontrado;
+    };
+
+    let logro = &ob["credentialSubject"]["achievement"];
+    if logro["name"] != insignia.logro.as_str() {
+        return ResultadoVc::NoCoincide("achievement.name");
+    }
+    if logro["description"] != documento.titulo.as_str() {
+        return ResultadoVc::NoCoincide("achievement.description");
+    }
+    if logro["criteria"]["narrative"] != insignia.criterios.as_str() {
+        return ResultadoVc::NoCoincide("achievement.criteria.narrative");
+    }
+    if logro["image"]["hashImagen"] != hex::encode(insignia.hash_imagen).as_str() {
+        return ResultadoVc::NoCoincide("achievement.image.hashImagen");
+    }
+    if let Some(imagen) = imagen {
+        if Sha256::digest(imagen).as_slice() != insignia.hash_imagen {
+            return ResultadoVc::NoCoincide("achievement.image");
+        }
+    }
+    ResultadoVc::Valida
+}
@@ -0,0 +1,124 @@
+//! Credenciales portables a partir de los documentos de `DocumentosContract`.
+//!
+//! * [`vc`]: W3C Verifiable Credentials (JSON-LD) cuyo `credentialStatus`
+//!   apunta al estado del documento en el contrato, y su verificador.
+//! * [`insignia`]: Open Badges 3.0 para los documentos registrados con
+//!   `registrar_insignia`.
+//! * [`divulgacion`]: sales, compromisos y paquetes de divulgación selectiva
+//!   que se comprueban con `verificar_campo`.
+
+use certificado::{DocumentosContractClient, MotivoRechazo};
+use soroban_sdk::{BytesN, Symbol};
+
+pub mod divulgacion;
+pub mod insignia;
+pub mod vc;
+
+#[cfg(test)]
+mod test;
+
+/// Estado que marca un documento como revocado en el contrato.
+pub const ESTADO_REVOCADO: &str = "Revocado";
+
+/// Un documento tal como lo devuelve `obtener_documento`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Documento {
+    pub id: i32,
+    pub titulo: String,
+    pub estado: String,
+    pub fecha_emision_academica: u64,
+}
+
+/// Datos de insignia tal como los devuelve `obtener_insignia`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Insignia {
+    pub logro: String,
+    pub criterios: String,
+    pub hash_imagen: [u8; 32],
+}
+
+/// Acceso de sólo lectura a un registro de documentos.
+pub trait RegistroDocumentos {
+    /// ID del contrato (strkey `C...`).
+    fn contrato(&self) -> String;
+    fn obtener_documento(&self, id: i32) -> Option<Documento>;
+    /// Motivos por los que `verificar_documento_publico` rechaza el
+    /// documento; vacío si es válido.
+    fn motivos_rechazo(&self, id: i32) -> Vec<MotivoRechazo>;
+    fn obtener_insignia(&self, id: i32) -> Option<Insignia>;
+    /// `false` también si `nombre` no es un nombre de campo válido (ver
+    /// [`nombre_campo_valido`]).
+    fn verificar_campo(&self, id: i32, nombre: &str, valor: &str, sal: &[u8; 32]) -> bool;
+}
+
+impl RegistroDocumentos for DocumentosContractClient<'_> {
+    fn contrato(&self) -> String {
+        self.address.to_string().to_string()
+    }
+
+    fn obtener_documento(&self, id: i32) -> Option<Documento> {
+        DocumentosContractClient::obtener_documento(self, &id).map(|(titulo, estado, fecha_emision_academica)| {
+            Documento {
+                id,
+                titulo: titulo.to_string(),
+                estado: estado.to_string(),
+                fecha_emision_academica,
+            }
+        })
+    }
+
+    fn motivos_rechazo(&self, id: i32) -> Vec<MotivoRechazo> {
+        DocumentosContractClient::verificar_documento_publico(self, &id, &None)
+            .motivos
+            .iter()
+            .collect()
+    }
+
+    fn obtener_insignia(&self, id: i32) -> Option<Insignia> {
+        DocumentosContractClient::obtener_insignia(self, &id).map(|insignia| Insignia {
+            logro: insignia.logro.to_string(),
+            criterios: insignia.criterios.to_string(),
+            hash_imagen: insignia.hash_imagen.to_array(),
+        })
+    }
+
+    fn verificar_campo(&self, id: i32, nombre: &str, valor: &str, sal: &[u8; 32]) -> bool {
+        if !nombre_campo_valido(nombre) {
+            return false;
+        }
+        DocumentosContractClient::verificar_campo(
+            self,
+            &id,
+            &Symbol::new(&self.env, nombre),
+            &soroban_sdk::String::from_str(&self.env, valor),
+            &BytesN::from_array(&self.env, sal),
+        )
+    }
+}
+
+/// Si `nombre` puede ser el `Symbol` de un campo: de 1 a 32 caracteres
+/// `a-z`, `A-Z`, `0-9` o `_`.
+pub fn nombre_campo_valido(nombre: &str) -> bool {
+    (1..=32).contains(&nombre.len()) && nombre.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_')
+}
+
+/// Timestamp Unix en formato RFC 3339 (UTC), p. ej. `2022-01-01T00:00:00Z`.
+pub fn fecha_iso(timestamp: u64) -> String {
+    // Algoritmo "civil from days" de Howard Hinnant
+    let segundos = timestamp % 86_400;
+    let dias = (timestamp / 86_400) as i64 + 719_468;
+    let era = dias / 146_097;
+    let dia_era = dias - era * 146_097;
+    let anio_era = (dia_era - dia_era / 1_460 + dia_era / 36_524 - dia_era / 146_096) / 365;
+    let dia_anio = dia_era - (365 * anio_era + anio_era / 4 - anio_era / 100);
+    let mp = (5 * dia_anio + 2) / 153;
+    let dia = dia_anio - (153 * mp + 2) / 5 + 1;
+    let mes = if mp < 10 { mp + 3 } else { mp - 9 };
+    let anio = anio_era + era * 400 + if mes <= 2 { 1 } else { 0 };
+    format!(
+        "{anio:04}-{mes:02}-{dia:02}T{:02}:{:02}:{:02}Z",
+        segundos / 3_600,
+        segundos % 3_600 / 60,
+        segundos % 60
+    )
+}
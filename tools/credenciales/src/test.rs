@@ -0,0 +1,312 @@
+#![cfg(test)]
+
+use super::*;
+use certificado::{DocumentosContract, MotivoRechazo};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    BytesN, Env, String as SorobanString,
+};
+
+/// Registro con el documento 1, dado de alta por el administrador.
+fn registro_con_documento(env: &Env) -> DocumentosContractClient<'_> {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let admin = soroban_sdk::Address::generate(env);
+    let contract_id = env.register(DocumentosContract, (admin.clone(),));
+    let cliente = DocumentosContractClient::new(env, &contract_id);
+    cliente.registrar_documento(
+        &1,
+        &admin,
+        &SorobanString::from_str(env, "Ingeniería en Sistemas"),
+        &SorobanString::from_str(env, "Activo"),
+        &1640995200,
+    );
+    cliente
+}
+
+/// Da de alta el perfil de un emisor nuevo. Requiere `mock_all_auths`.
+fn alta_emisor(env: &Env, registro: &DocumentosContractClient) -> soroban_sdk::Address {
+    let emisor = soroban_sdk::Address::generate(env);
+    registro.crear_usuario(&emisor, &None, &None, &SorobanString::from_str(env, "Emisor"));
+    emisor
+}
+
+fn opciones() -> vc::Opciones {
+    vc::Opciones {
+        red: "testnet".into(),
+        nombre_emisor: Some("Universidad de Prueba".into()),
+        titular: Some("did:example:alumno-42".into()),
+    }
+}
+
+#[test]
+fn test_fecha_iso() {
+    assert_eq!(fecha_iso(0), "1970-01-01T00:00:00Z");
+    assert_eq!(fecha_iso(1640995200), "2022-01-01T00:00:00Z");
+    assert_eq!(fecha_iso(1709210096), "2024-02-29T12:34:56Z");
+}
+
+#[test]
+fn test_exportar_vc() {
+    let env = Env::default();
+    let registro = registro_con_documento(&env);
+
+    let credencial = vc::exportar(&registro, 1, &opciones()).unwrap();
+    let contrato = registro.contrato();
+
+    assert_eq!(credencial["@context"][0], "https://www.w3.org/ns/credentials/v2");
+    assert_eq!(credencial["type"][0], "VerifiableCredential");
+    assert_eq!(credencial["issuer"]["id"], format!("stellar:testnet:{contrato}"));
+    assert_eq!(credencial["issuer"]["name"], "Universidad de Prueba");
+    assert_eq!(credencial["validFrom"], "2022-01-01T00:00:00Z");
+    assert_eq!(credencial["credentialSubject"]["id"], "did:example:alumno-42");
+    assert_eq!(credencial["credentialSubject"]["titulo"], "Ingeniería en Sistemas");
+    assert_eq!(credencial["credentialStatus"]["type"], vc::TIPO_ESTADO);
+    assert_eq!(credencial["credentialStatus"]["contrato"], contrato);
+    assert_eq!(credencial["credentialStatus"]["idDocumento"], 1);
+
+    assert!(vc::exportar(&registro, 999, &opciones()).is_none());
+}
+
+#[test]
+fn test_verificar_vc() {
+    let env = Env::default();
+    let registro = registro_con_documento(&env);
+    let credencial = vc::exportar(&registro, 1, &opciones()).unwrap();
+
+    assert_eq!(vc::verificar(&credencial, &registro), vc::ResultadoVc::Valida);
+
+    // Un cambio de estado que no es revocación no invalida la credencial
+    registro.actualizar_estado(&1, &registro.obtener_admin(), &SorobanString::from_str(&env, "Validado"));
+    assert_eq!(vc::verificar(&credencial, &registro), vc::ResultadoVc::Valida);
+
+    let mut alterada = credencial.clone();
+    alterada["credentialSubject"]["titulo"] = "Medicina".into();
+    assert_eq!(
+        vc::verificar(&alterada, &registro),
+        vc::ResultadoVc::NoCoincide("credentialSubject.titulo")
+    );
+
+    let mut inexistente = credencial.clone();
+    inexistente["credentialStatus"]["idDocumento"] = 999.into();
+    assert_eq!(vc::verificar(&inexistente, &registro), vc::ResultadoVc::NoEncontrado);
+
+    registro.actualizar_estado(&1, &registro.obtener_admin(), &SorobanString::from_str(&env, ESTADO_REVOCADO));
+    assert_eq!(
+        vc::verificar(&credencial, &registro),
+        vc::ResultadoVc::Rechazado(vec![MotivoRechazo::Revocado])
+    );
+}
+
+#[test]
+fn test_verificar_vc_vencida() {
+    let env = Env::default();
+    let registro = registro_con_documento(&env);
+    registro.registrar_emision(
+        &1,
+        &alta_emisor(&env, &registro),
+        &None,
+        &BytesN::from_array(&env, &[1; 32]),
+        &BytesN::from_array(&env, &[2; 32]),
+        &Some(1_700_000_000 + 86_400),
+    );
+    let credencial = vc::exportar(&registro, 1, &opciones()).unwrap();
+    assert_eq!(vc::verificar(&credencial, &registro), vc::ResultadoVc::Valida);
+
+    // El contrato la rechaza aunque el estado siga siendo "Activo"
+    env.ledger().set_timestamp(1_700_000_000 + 2 * 86_400);
+    assert_eq!(
+        vc::verificar(&credencial, &registro),
+        vc::ResultadoVc::Rechazado(vec![MotivoRechazo::Vencido])
+    );
+}
+
+#[test]
+fn test_verificar_vc_otro_contrato() {
+    let env = Env::default();
+    let registro = registro_con_documento(&env);
+    let otro_registro = registro_con_documento(&env);
+    let credencial = vc::exportar(&registro, 1, &opciones()).unwrap();
+
+    assert_eq!(
+        vc::verificar(&credencial, &otro_registro),
+        vc::ResultadoVc::OtroContrato(registro.contrato())
+    );
+
+    let mut sin_estado = credencial;
+    sin_estado["credentialStatus"] = serde_json::Value::Null;
+    assert!(matches!(
+        vc::verificar(&sin_estado, &registro),
+        vc::ResultadoVc::Formato(_)
+    ));
+}
+
+fn registro_con_insignia<'a>(env: &'a Env, imagen: &[u8]) -> DocumentosContractClient<'a> {
+    use sha2::{Digest, Sha256};
+
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+    let admin = soroban_sdk::Address::generate(env);
+    let contract_id = env.register(DocumentosContract, (admin.clone(),));
+    let cliente = DocumentosContractClient::new(env, &contract_id);
+    let hash: [u8; 32] = Sha256::digest(imagen).into();
+    cliente.registrar_insignia(
+        &1,
+        &admin,
+        &SorobanString::from_str(env, "Taller de Rust"),
+        &SorobanString::from_str(env, "Activo"),
+        &1640995200,
+        &certificado::Insignia {
+            logro: SorobanString::from_str(env, "Programador Rust"),
+            criterios: SorobanString::from_str(env, "Completar los 12 ejercicios del taller"),
+            hash_imagen: BytesN::from_array(env, &hash),
+        },
+    );
+    cliente
+}
+
+#[test]
+fn test_exportar_insignia() {
+    let env = Env::default();
+    let imagen = b"imagen de la insignia";
+    let registro = registro_con_insignia(&env, imagen);
+    let url = "https://certiweb.example/insignias/1.png";
+
+    let ob = insignia::exportar(&registro, 1, url, &opciones()).unwrap();
+    assert_eq!(ob["@context"][1], insignia::CONTEXTO_OB3);
+    assert_eq!(ob["type"][1], "OpenBadgeCredential");
+    assert_eq!(ob["issuer"]["type"][0], "Profile");
+    assert_eq!(ob["credentialSubject"]["type"][0], "AchievementSubject");
+    assert_eq!(ob["credentialSubject"]["id"], "did:example:alumno-42");
+
+    let logro = &ob["credentialSubject"]["achievement"];
+    assert_eq!(logro["name"], "Programador Rust");
+    assert_eq!(logro["description"], "Taller de Rust");
+    assert_eq!(
+        logro["criteria"]["narrative"],
+        "Completar los 12 ejercicios del taller"
+    );
+    assert_eq!(logro["image"]["id"], url);
+
+    // Un documento sin datos de insignia no se exporta como insignia
+    registro.registrar_documento(
+        &2,
+        &registro.obtener_admin(),
+        &SorobanString::from_str(&env, "Ingeniería en Sistemas"),
+        &SorobanString::from_str(&env, "Activo"),
+        &1640995200,
+    );
+    assert!(insignia::exportar(&registro, 2, url, &opciones()).is_none());
+}
+
+#[test]
+fn test_verificar_insignia() {
+    let env = Env::default();
+    let imagen = b"imagen de la insignia";
+    let registro = registro_con_insignia(&env, imagen);
+    let ob =
+        insignia::exportar(&registro, 1, "https://certiweb.example/1.png", &opciones()).unwrap();
+
+    assert_eq!(
+        insignia::verificar(&ob, &registro, None),
+        vc::ResultadoVc::Valida
+    );
+    assert_eq!(
+        insignia::verificar(&ob, &registro, Some(imagen)),
+        vc::ResultadoVc::Valida
+    );
+    assert_eq!(
+        insignia::verificar(&ob, &registro, Some(b"otra imagen")),
+        vc::ResultadoVc::NoCoincide("achievement.image")
+    );
+
+    let mut alterada = ob.clone();
+    alterada["credentialSubject"]["achievement"]["criteria"]["narrative"] = "Asistir".into();
+    assert_eq!(
+        insignia::verificar(&alterada, &registro, None),
+        vc::ResultadoVc::NoCoincide("achievement.criteria.narrative")
+    );
+
+    registro.actualizar_estado(&1, &registro.obtener_admin(), &SorobanString::from_str(&env, ESTADO_REVOCADO));
+    assert_eq!(
+        insignia::verificar(&ob, &registro, None),
+        vc::ResultadoVc::Rechazado(vec![MotivoRechazo::Revocado])
+    );
+}
+
+#[test]
+fn test_preparar_sales() {
+    let campos = divulgacion::preparar(&[("carrera", "Ingeniería en Sistemas"), ("promedio", "9.4")]).unwrap();
+    assert_eq!(campos[0].nombre, "carrera");
+    assert_eq!(campos[1].valor, "9.4");
+    assert_ne!(campos[0].sal, campos[1].sal);
+}
+
+#[test]
+fn test_divulgacion_selectiva() {
+    let env = Env::default();
+    let registro = registro_con_documento(&env);
+    let emisor = alta_emisor(&env, &registro);
+    registro.registrar_emision(
+        &1,
+        &emisor,
+        &None,
+        &BytesN::from_array(&env, &[3; 32]),
+        &BytesN::from_array(&env, &[7; 32]),
+        &None,
+    );
+
+    // Sales fijas para que la prueba (y su snapshot) sea reproducible
+    let campos: Vec<divulgacion::Campo> = [
+        ("carrera", "Ingeniería en Sistemas"),
+        ("institucion", "Universidad de Prueba"),
+        ("promedio", "9.4"),
+    ]
+    .iter()
+    .zip(1u8..)
+    .map(|((nombre, valor), sal)| divulgacion::Campo {
+        nombre: nombre.to_string(),
+        valor: valor.to_string(),
+        sal: [sal; 32],
+    })
+    .collect();
+
+    let mut compromisos = soroban_sdk::Map::new(&env);
+    for (nombre, compromiso) in divulgacion::compromisos(&campos) {
+        compromisos.set(
+            soroban_sdk::Symbol::new(&env, &nombre),
+            BytesN::from_array(&env, &compromiso),
+        );
+    }
+    registro.registrar_compromisos(&1, &compromisos);
+
+    // El titular muestra la carrera y la institución, pero no el promedio
+    let paquete = divulgacion::paquete(&registro.contrato(), 1, &campos, &["carrera", "institucion"]);
+    assert_eq!(paquete["campos"].as_array().unwrap().len(), 2);
+    let divulgados = divulgacion::verificar_paquete(&paquete, &registro).unwrap();
+    assert_eq!(divulgados["carrera"], "Ingeniería en Sistemas");
+    assert!(!divulgados.contains_key("promedio"));
+
+    let mut alterado = paquete.clone();
+    alterado["campos"][0]["valor"] = "Medicina".into();
+    assert_eq!(
+        divulgacion::verificar_paquete(&alterado, &registro),
+        Err(divulgacion::Error::CampoNoCoincide("carrera".into()))
+    );
+
+    // Nombres que no pueden ser un Symbol y paquetes vacíos son de formato
+    for nombre in ["año de egreso", "", &"x".repeat(33)] {
+        let mut raro = paquete.clone();
+        raro["campos"][0]["nombre"] = nombre.into();
+        assert!(matches!(divulgacion::verificar_paquete(&raro, &registro), Err(divulgacion::Error::Formato(_))));
+    }
+    let vacio = divulgacion::paquete(&registro.contrato(), 1, &campos, &[]);
+    assert!(matches!(divulgacion::verificar_paquete(&vacio, &registro), Err(divulgacion::Error::Formato(_))));
+
+    // Un documento revocado ya no respalda sus campos
+    registro.actualizar_estado(&1, &registro.obtener_admin(), &SorobanString::from_str(&env, ESTADO_REVOCADO));
+    assert_eq!(
+        divulgacion::verificar_paquete(&paquete, &registro),
+        Err(divulgacion::Error::Rechazado(vec![MotivoRechazo::Revocado]))
+    );
+}
@@ -0,0 +1,142 @@
+//! W3C Verifiable Credentials (Data Model 2.0).
+//!
+//! La credencial no lleva `proof` propio: su validez se comprueba contra el
+//! contrato. `credentialStatus` indica el contrato y el documento a consultar
+//! con `verificar_documento_publico`, y [`verificar`] rechaza lo que rechace
+//! el contrato (revocado, vencido, emisor inactivo o no autorizado, ...) y
+//! compara los datos de la credencial con el registro.
+
+use certificado::MotivoRechazo;
+use serde_json::{json, Value};
+
+use crate::{fecha_iso, Documento, RegistroDocumentos};
+
+/// Tipo de `credentialStatus` propio de CertiWeb.
+pub const TIPO_ESTADO: &str = "CertiWebEstadoDocumento";
+
+/// Datos de emisión que no están en el contrato.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Opciones {
+    /// Red Stellar del contrato (`testnet`, `public`, ...).
+    pub red: String,
+    /// Nombre legible de la institución emisora.
+    pub nombre_emisor: Option<String>,
+    /// Identificador (DID o URI) del titular, si se conoce.
+    pub titular: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ResultadoVc {
+    Valida,
+    /// La credencial no tiene la forma esperada.
+    Formato(String),
+    /// La credencial pertenece a otro registro.
+    OtroContrato(String),
+    NoEncontrado,
+    /// El contrato rechaza el documento por estos motivos.
+    Rechazado(Vec<MotivoRechazo>),
+    /// El campo de la credencial no coincide con el registro.
+    NoCoincide(&'static str),
+}
+
+fn uri_contrato(red: &str, contrato: &str) -> String {
+    format!("stellar:{red}:{contrato}")
+}
+
+pub(crate) fn emisor(contrato: &str, opciones: &Opciones) -> Value {
+    let mut issuer = json!({ "id": uri_contrato(&opciones.red, contrato) });
+    if let Some(nombre) = &opciones.nombre_emisor {
+        issuer["name"] = json!(nombre);
+    }
+    issuer
+}
+
+pub(crate) fn estado_credencial(id: i32, contrato: &str, opciones: &Opciones) -> Value {
+    json!({
+        "id": format!("{}#documento-{id}", uri_contrato(&opciones.red, contrato)),
+        "type": TIPO_ESTADO,
+        "contrato": contrato,
+        "red": opciones.red,
+        "funcion": "verificar_documento_publico",
+        "idDocumento": id,
+    })
+}
+
+/// Lee `credentialStatus` y devuelve el documento vigente al que apunta, o el
+/// motivo por el que la credencial no es válida.
+pub(crate) fn documento_vigente(
+    vc: &Value,
+    registro: &impl RegistroDocumentos,
+) -> Result<Documento, ResultadoVc> {
+    let estado = &vc["credentialStatus"];
+    if estado["type"] != TIPO_ESTADO {
+        return Err(ResultadoVc::Formato("credentialStatus no es de CertiWeb".into()));
+    }
+    let Some(contrato) = estado["contrato"].as_str() else {
+        return Err(ResultadoVc::Formato("falta credentialStatus.contrato".into()));
+    };
+    if contrato != registro.contrato() {
+        return Err(ResultadoVc::OtroContrato(contrato.to_string()));
+    }
+    let Some(id) = estado["idDocumento"].as_i64().and_then(|id| i32::try_from(id).ok()) else {
+        return Err(ResultadoVc::Formato("falta credentialStatus.idDocumento".into()));
+    };
+
+    let motivos = registro.motivos_rechazo(id);
+    if motivos.contains(&MotivoRechazo::NoEncontrado) {
+        return Err(ResultadoVc::NoEncontrado);
+    }
+    if !motivos.is_empty() {
+        return Err(ResultadoVc::Rechazado(motivos));
+    }
+    let Some(documento) = registro.obtener_documento(id) else {
+        return Err(ResultadoVc::NoEncontrado);
+    };
+    if vc["validFrom"] != fecha_iso(documento.fecha_emision_academica).as_str() {
+        return Err(ResultadoVc::NoCoincide("validFrom"));
+    }
+    Ok(documento)
+}
+
+/// Construye la credencial de un documento leído del contrato.
+pub fn credencial(documento: &Documento, contrato: &str, opciones: &Opciones) -> Value {
+    let mut sujeto = json!({
+        "type": "DocumentoAcademico",
+        "titulo": documento.titulo,
+        "fechaEmision": fecha_iso(documento.fecha_emision_academica),
+    });
+    if let Some(titular) = &opciones.titular {
+        sujeto["id"] = json!(titular);
+    }
+
+    json!({
+        "@context": [
+            "https://www.w3.org/ns/credentials/v2",
+            { "@vocab": "https://certiweb.example/vocab#" }
+        ],
+        "id": format!("urn:certiweb:{contrato}:{}", documento.id),
+        "type": ["VerifiableCredential", "CertiWebDocumento"],
+        "issuer": emisor(contrato, opciones),
+        "validFrom": fecha_iso(documento.fecha_emision_academica),
+        "credentialSubject": sujeto,
+        "credentialStatus": estado_credencial(documento.id, contrato, opciones),
+    })
+}
+
+/// Lee el documento del registro y construye su credencial.
+pub fn exportar(registro: &impl RegistroDocumentos, id: i32, opciones: &Opciones) -> Option<Value> {
+    let documento = registro.obtener_documento(id)?;
+    Some(credencial(&documento, &registro.contrato(), opciones))
+}
+
+/// Comprueba una credencial contra el estado actual del contrato.
+pub fn verificar(vc: &Value, registro: &impl RegistroDocumentos) -> ResultadoVc {
+    let documento = match documento_vigente(vc, registro) {
+        Ok(documento) => documento,
+        Err(resultado) => return resultado,
+    };
+    if vc["credentialSubject"]["titulo"] != documento.titulo.as_str() {
+        return ResultadoVc::NoCoincide("credentialSubject.titulo");
+    }
+    ResultadoVc::Valida
+}
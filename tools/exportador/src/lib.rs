@@ -0,0 +1,488 @@
+//! Exportación fuera de la cadena del registro de `DocumentosContract`.
+//!
+//! Lee el almacenamiento del contrato desde un snapshot del ledger (el JSON que
+//! produce `stellar snapshot create`, o los snapshots de prueba bajo
+//! `contracts/certificado/test_snapshots/`) y lo vuelca en tablas de
+//! documentos, historial, usuarios e instituciones, como JSON Lines o CSV.
+//!
+//! El resumen de cada exportación (hash SHA-256 del contenido y número de
+//! filas) es lo que se registra en la cadena con `exportar_datos`, y el digest
+//! de los documentos es el mismo que guarda `realizar_respaldo`, de modo que
+//! una exportación se contrasta con el que devuelve `consultar_respaldo`:
+//! [`verificar_exportacion`] lo recalcula a partir del fichero de documentos
+//! exportado, sin necesidad del snapshot.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use soroban_ledger_snapshot::LedgerSnapshot;
+use stellar_xdr::curr::{LedgerEntryData, Limits, ScString, ScVal, ScVec, WriteXdr};
+
+/// Tablas exportadas: nombre, clave de almacenamiento en el contrato y nombres
+/// de las columnas cuando el valor guardado es una tupla.
+pub const TABLAS: &[(&str, &str, &[&str])] = &[
+    ("documentos", "DOCUMENTS", &["titulo", "estado", "fecha_emision_academica"]),
+    ("historial", "HISTORIAL", &["fecha", "resultado"]),
+    ("usuarios", "USUARIOS", &[]),
+    ("instituciones", "INSTITUC", &[]),
+];
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Snapshot(String),
+    ContratoNoEncontrado,
+    ContratoAmbiguo(Vec<String>),
+    /// El snapshot no tiene un respaldo con ese ID.
+    RespaldoNoEncontrado(i32),
+    /// El fichero exportado no tiene la forma de la tabla de documentos.
+    Exportacion(String),
+    /// Un documento no se pudo serializar para el digest.
+    Digest(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "error de E/S: {e}"),
+            Error::Snapshot(e) => write!(f, "snapshot inválido: {e}"),
+            Error::ContratoNoEncontrado => write!(f, "no hay datos del contrato en el snapshot"),
+            Error::ContratoAmbiguo(contratos) => write!(
+                f,
+                "el snapshot contiene varios registros, indique uno con --contrato: {}",
+                contratos.join(", ")
+            ),
+            Error::RespaldoNoEncontrado(id) => write!(f, "no hay un respaldo {id} en el snapshot"),
+            Error::Exportacion(e) => write!(f, "exportación inválida: {e}"),
+            Error::Digest(e) => write!(f, "no se pudo calcular el digest: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Formato {
+    Jsonl,
+    Csv,
+}
+
+impl Formato {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Formato::Jsonl => "jsonl",
+            Formato::Csv => "csv",
+        }
+    }
+
+    /// Formato de un fichero exportado según su extensión.
+    pub fn de_ruta(ruta: &Path) -> Option<Formato> {
+        match ruta.extension()?.to_str()? {
+            "jsonl" => Some(Formato::Jsonl),
+            "csv" => Some(Formato::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Una tabla exportada, con sus columnas en orden estable (`id` primero).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tabla {
+    pub nombre: &'static str,
+    pub columnas: Vec<String>,
+    pub filas: Vec<Vec<Value>>,
+}
+
+/// Lo que se registra en la cadena con `exportar_datos`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Resumen {
+    pub hash: [u8; 32],
+    pub filas: u32,
+}
+
+/// Almacenamiento de un contrato: clave (símbolo) -> valor.
+pub type Datos = BTreeMap<String, ScVal>;
+
+/// Lee un snapshot del ledger o un snapshot de prueba del SDK (que envuelve el
+/// ledger bajo la clave `ledger`).
+pub fn leer_snapshot(ruta: impl AsRef<Path>) -> Result<LedgerSnapshot, Error> {
+    let json: Value = serde_json::from_reader(File::open(ruta)?)
+        .map_err(|e| Error::Snapshot(e.to_string()))?;
+    let ledger = match json.get("ledger") {
+        Some(ledger) => ledger.clone(),
+        None => json,
+    };
+    serde_json::from_value(ledger).map_err(|e| Error::Snapshot(e.to_string()))
+}
+
+/// Extrae las entradas del registro (las claves de `TABLAS`) de un contrato.
+/// Si `contrato` es `None`, el snapshot debe contener un único registro.
+pub fn datos_contrato(snapshot: &LedgerSnapshot, contrato: Option<&str>) -> Result<Datos, Error> {
+    let claves: Vec<&str> = TABLAS.iter().map(|(_, clave, _)| *clave).collect();
+    entradas_contrato(snapshot, contrato, &claves)
+}
+
+/// Digest de documentos que guardó `realizar_respaldo` en el respaldo
+/// `id_respaldo`, el mismo que devuelve `consultar_respaldo`.
+pub fn digest_respaldo(snapshot: &LedgerSnapshot, contrato: Option<&str>, id_respaldo: i32) -> Result<[u8; 32], Error> {
+    let datos = entradas_contrato(snapshot, contrato, &["RESPALDOS"])?;
+    let respaldo = entradas(datos.get("RESPALDOS"))
+        .into_iter()
+        .find(|(id, _)| **id == ScVal::I32(id_respaldo))
+        .map(|(_, respaldo)| respaldo)
+        .ok_or(Error::RespaldoNoEncontrado(id_respaldo))?;
+    let ScVal::Map(Some(campos)) = respaldo else {
+        return Err(Error::Snapshot(format!("respaldo {id_respaldo} mal formado")));
+    };
+    campos
+        .iter()
+        .find(|campo| nombre_clave(&campo.key) == "digest")
+        .and_then(|campo| match &campo.val {
+            ScVal::Bytes(digest) => digest.as_slice().try_into().ok(),
+            _ => None,
+        })
+        .ok_or_else(|| Error::Snapshot(format!("respaldo {id_respaldo} sin digest")))
+}
+
+fn entradas_contrato(snapshot: &LedgerSnapshot, contrato: Option<&str>, claves: &[&str]) -> Result<Datos, Error> {
+    let mut por_contrato: BTreeMap<String, Datos> = BTreeMap::new();
+
+    for (_, (entrada, _)) in snapshot.entries() {
+        let LedgerEntryData::ContractData(datos) = &entrada.data else {
+            continue;
+        };
+        let ScVal::Symbol(clave) = &datos.key else {
+            continue;
+        };
+        let clave = clave.to_utf8_string_lossy();
+        if !claves.contains(&clave.as_str()) {
+            continue;
+        }
+        por_contrato
+            .entry(datos.contract.to_string())
+            .or_default()
+            .insert(clave, datos.val.clone());
+    }
+
+    match contrato {
+        Some(contrato) => por_contrato.remove(contrato).ok_or(Error::ContratoNoEncontrado),
+        None if por_contrato.len() > 1 => {
+            Err(Error::ContratoAmbiguo(por_contrato.into_keys().collect()))
+        }
+        None => por_contrato
+            .into_values()
+            .next()
+            .ok_or(Error::ContratoNoEncontrado),
+    }
+}
+
+/// Convierte el almacenamiento en las tablas de `TABLAS`, en ese orden.
+/// Las claves ausentes producen tablas vacías.
+pub fn extraer_tablas(datos: &Datos) -> Vec<Tabla> {
+    TABLAS
+        .iter()
+        .map(|(nombre, clave, columnas_tupla)| {
+            let mut columnas = vec!["id".to_string()];
+            let mut filas_con_nombre = Vec::new();
+
+            for (id, valor) in entradas(datos.get(*clave)) {
+                let mut fila = vec![("id".to_string(), valor_json(id))];
+                match valor {
+                    ScVal::Vec(Some(tupla)) if !columnas_tupla.is_empty() => {
+                        for (i, campo) in tupla.iter().enumerate() {
+                            let columna = columnas_tupla
+                                .get(i)
+                                .map(|c| c.to_string())
+                                .unwrap_or_else(|| format!("campo_{i}"));
+                            fila.push((columna, valor_json(campo)));
+                        }
+                    }
+                    ScVal::Map(Some(campos)) => {
+                        for campo in campos.iter() {
+                            fila.push((nombre_clave(&campo.key), valor_json(&campo.val)));
+                        }
+                    }
+                    otro => fila.push(("valor".to_string(), valor_json(otro))),
+                }
+                for (columna, _) in &fila {
+                    if !columnas.contains(columna) {
+                        columnas.push(columna.clone());
+                    }
+                }
+                filas_con_nombre.push(fila);
+            }
+
+            let filas = filas_con_nombre
+                .into_iter()
+                .map(|fila| {
+                    columnas
+                        .iter()
+                        .map(|columna| {
+                            fila.iter()
+                                .find(|(c, _)| c == columna)
+                                .map(|(_, v)| v.clone())
+                                .unwrap_or(Value::Null)
+                        })
+                        .collect()
+                })
+                .collect();
+
+            Tabla {
+                nombre,
+                columnas,
+                filas,
+            }
+        })
+        .collect()
+}
+
+/// Escribe una tabla en el formato indicado.
+pub fn escribir(tabla: &Tabla, formato: Formato, mut salida: impl Write) -> io::Result<()> {
+    match formato {
+        Formato::Jsonl => {
+            for fila in &tabla.filas {
+                let campos: Vec<String> = tabla
+                    .columnas
+                    .iter()
+                    .zip(fila)
+                    .map(|(columna, valor)| format!("{}:{}", Value::from(columna.as_str()), valor))
+                    .collect();
+                writeln!(salida, "{{{}}}", campos.join(","))?;
+            }
+        }
+        Formato::Csv => {
+            let encabezado: Vec<String> = tabla.columnas.iter().map(|c| celda_csv(c)).collect();
+            writeln!(salida, "{}", encabezado.join(","))?;
+            for fila in &tabla.filas {
+                let celdas: Vec<String> = fila
+                    .iter()
+                    .map(|valor| match valor {
+                        Value::Null => String::new(),
+                        Value::String(s) => celda_csv(s),
+                        otro => celda_csv(&otro.to_string()),
+                    })
+                    .collect();
+                writeln!(salida, "{}", celdas.join(","))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Escribe cada tabla en `<directorio>/<tabla>.<extension>` y devuelve el
+/// resumen de la exportación: SHA-256 de todos los bytes escritos, en el orden
+/// de `TABLAS`, y el total de filas.
+pub fn exportar(tablas: &[Tabla], formato: Formato, directorio: &Path) -> Result<Resumen, Error> {
+    fs::create_dir_all(directorio)?;
+    let mut hasher = Sha256::new();
+    let mut filas = 0;
+    for tabla in tablas {
+        let mut contenido = Vec::new();
+        escribir(tabla, formato, &mut contenido)?;
+        fs::write(
+            directorio.join(format!("{}.{}", tabla.nombre, formato.extension())),
+            &contenido,
+        )?;
+        hasher.update(&contenido);
+        filas += tabla.filas.len() as u32;
+    }
+    Ok(Resumen {
+        hash: hasher.finalize().into(),
+        filas,
+    })
+}
+
+/// Digest de los documentos tal como lo calcula `realizar_respaldo`:
+/// `h_i = sha256(h_{i-1} || sha256(xdr((id, titulo, estado, fecha))))`.
+pub fn digest_documentos(datos: &Datos) -> Result<[u8; 32], Error> {
+    let hojas = entradas(datos.get("DOCUMENTS")).into_iter().map(|(id, valor)| {
+        let mut campos = vec![id.clone()];
+        if let ScVal::Vec(Some(tupla)) = valor {
+            campos.extend(tupla.iter().cloned());
+        }
+        campos
+    });
+    encadenar(hojas)
+}
+
+/// Recalcula el digest de documentos a partir de la tabla `documentos`
+/// exportada (`documentos.jsonl` o `documentos.csv`) y lo compara con el que
+/// registró `realizar_respaldo` (ver [`digest_respaldo`]).
+pub fn verificar_exportacion(ruta: &Path, formato: Formato, digest: &[u8; 32]) -> Result<bool, Error> {
+    let texto = fs::read_to_string(ruta)?;
+    let filas = match formato {
+        Formato::Jsonl => texto
+            .lines()
+            .filter(|linea| !linea.trim().is_empty())
+            .map(|linea| match serde_json::from_str(linea) {
+                Ok(Value::Object(fila)) => Ok(fila),
+                _ => Err(Error::Exportacion(format!("línea JSON inválida: {linea}"))),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Formato::Csv => {
+            let mut registros = registros_csv(&texto)?.into_iter();
+            let encabezado = registros
+                .next()
+                .ok_or_else(|| Error::Exportacion("falta el encabezado".into()))?;
+            registros
+                .map(|celdas| {
+                    encabezado
+                        .iter()
+                        .cloned()
+                        .zip(celdas.into_iter().map(Value::String))
+                        .collect()
+                })
+                .collect()
+        }
+    };
+
+    let mut hojas = BTreeMap::new();
+    for fila in &filas {
+        let id = entero(fila, "id")?;
+        let id = i32::try_from(id).map_err(|_| Error::Exportacion(format!("id fuera de rango: {id}")))?;
+        let fecha = entero(fila, "fecha_emision_academica")?;
+        let fecha = u64::try_from(fecha).map_err(|_| Error::Exportacion(format!("fecha negativa en {id}")))?;
+        let hoja = vec![
+            ScVal::I32(id),
+            texto_xdr(fila, "titulo")?,
+            texto_xdr(fila, "estado")?,
+            ScVal::U64(fecha),
+        ];
+        if hojas.insert(id, hoja).is_some() {
+            return Err(Error::Exportacion(format!("documento {id} repetido")));
+        }
+    }
+    // El contrato recorre los documentos en orden de ID
+    Ok(encadenar(hojas.into_values())? == *digest)
+}
+
+fn encadenar(hojas: impl Iterator<Item = Vec<ScVal>>) -> Result<[u8; 32], Error> {
+    let mut acumulado = [0u8; 32];
+    for campos in hojas {
+        let tupla: ScVec = campos.try_into().map_err(|e| Error::Digest(format!("{e:?}")))?;
+        let hoja = ScVal::Vec(Some(tupla))
+            .to_xdr(Limits::none())
+            .map_err(|e| Error::Digest(e.to_string()))?;
+        let mut hasher = Sha256::new();
+        hasher.update(acumulado);
+        hasher.update(Sha256::digest(hoja));
+        acumulado = hasher.finalize().into();
+    }
+    Ok(acumulado)
+}
+
+fn entero(fila: &serde_json::Map<String, Value>, columna: &str) -> Result<i64, Error> {
+    match fila.get(columna) {
+        Some(Value::Number(n)) => n.as_i64(),
+        Some(Value::String(s)) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| Error::Exportacion(format!("{columna} ausente o no numérico")))
+}
+
+fn texto_xdr(fila: &serde_json::Map<String, Value>, columna: &str) -> Result<ScVal, Error> {
+    let Some(Value::String(texto)) = fila.get(columna) else {
+        return Err(Error::Exportacion(format!("{columna} ausente")));
+    };
+    let texto = texto.clone().try_into().map_err(|e| Error::Digest(format!("{e:?}")))?;
+    Ok(ScVal::String(ScString(texto)))
+}
+
+/// Registros de un CSV escrito por [`escribir`]: celdas separadas por comas,
+/// entre comillas (dobladas dentro) si contienen comas, comillas o saltos.
+fn registros_csv(texto: &str) -> Result<Vec<Vec<String>>, Error> {
+    let mut registros = Vec::new();
+    let mut registro = Vec::new();
+    let mut celda = String::new();
+    let mut entre_comillas = false;
+    let mut caracteres = texto.chars().peekable();
+    while let Some(c) = caracteres.next() {
+        match c {
+            '"' if entre_comillas && caracteres.peek() == Some(&'"') => {
+                caracteres.next();
+                celda.push('"');
+            }
+            '"' if entre_comillas => entre_comillas = false,
+            '"' if celda.is_empty() => entre_comillas = true,
+            ',' if !entre_comillas => registro.push(std::mem::take(&mut celda)),
+            '\n' if !entre_comillas => {
+                registro.push(std::mem::take(&mut celda));
+                registros.push(std::mem::take(&mut registro));
+            }
+            otro => celda.push(otro),
+        }
+    }
+    if entre_comillas {
+        return Err(Error::Exportacion("comillas sin cerrar".into()));
+    }
+    if !celda.is_empty() || !registro.is_empty() {
+        registro.push(celda);
+        registros.push(registro);
+    }
+    Ok(registros)
+}
+
+fn entradas(valor: Option<&ScVal>) -> Vec<(&ScVal, &ScVal)> {
+    match valor {
+        Some(ScVal::Map(Some(mapa))) => mapa.iter().map(|e| (&e.key, &e.val)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn nombre_clave(clave: &ScVal) -> String {
+    match clave {
+        ScVal::Symbol(s) => s.to_utf8_string_lossy(),
+        ScVal::String(s) => s.to_utf8_string_lossy(),
+        otro => valor_json(otro).to_string(),
+    }
+}
+
+fn valor_json(valor: &ScVal) -> Value {
+    match valor {
+        ScVal::Bool(b) => Value::Bool(*b),
+        ScVal::Void => Value::Null,
+        ScVal::U32(n) => Value::from(*n),
+        ScVal::I32(n) => Value::from(*n),
+        ScVal::U64(n) => Value::from(*n),
+        ScVal::I64(n) => Value::from(*n),
+        ScVal::Timepoint(t) => Value::from(t.0),
+        ScVal::Duration(d) => Value::from(d.0),
+        ScVal::U128(p) => Value::from((((p.hi as u128) << 64) | p.lo as u128).to_string()),
+        ScVal::I128(p) => Value::from((((p.hi as i128) << 64) | p.lo as i128).to_string()),
+        ScVal::Bytes(b) => Value::from(hex::encode(b.as_slice())),
+        ScVal::String(s) => Value::from(s.to_utf8_string_lossy()),
+        ScVal::Symbol(s) => Value::from(s.to_utf8_string_lossy()),
+        ScVal::Address(a) => Value::from(a.to_string()),
+        ScVal::Vec(Some(v)) => Value::Array(v.iter().map(valor_json).collect()),
+        ScVal::Vec(None) => Value::Array(Vec::new()),
+        ScVal::Map(Some(m)) => Value::Object(
+            m.iter()
+                .map(|e| (nombre_clave(&e.key), valor_json(&e.val)))
+                .collect(),
+        ),
+        ScVal::Map(None) => Value::Object(Default::default()),
+        otro => Value::from(format!("{otro:?}")),
+    }
+}
+
+fn celda_csv(texto: &str) -> String {
+    if texto.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", texto.replace('"', "\"\""))
+    } else {
+        texto.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test;
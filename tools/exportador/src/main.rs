@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use exportador::{
+    datos_contrato, digest_documentos, digest_respaldo, exportar, extraer_tablas, leer_snapshot,
+    verificar_exportacion, Formato,
+};
+
+const USO: &str = "uso: exportador <snapshot.json> [--contrato <C...>] [--formato jsonl|csv] [--salida <directorio>]
+       exportador verificar <documentos.jsonl|csv> (--digest <hex> | --snapshot <snapshot.json> --respaldo <id> [--contrato <C...>])";
+
+fn main() -> ExitCode {
+    if std::env::args().nth(1).as_deref() == Some("verificar") {
+        return verificar(std::env::args().skip(2));
+    }
+
+    let mut snapshot = None;
+    let mut contrato = None;
+    let mut formato = Formato::Jsonl;
+    let mut salida = PathBuf::from("exportacion");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--contrato" => contrato = args.next(),
+            "--formato" => {
+                formato = match args.next().as_deref() {
+                    Some("jsonl") => Formato::Jsonl,
+                    Some("csv") => Formato::Csv,
+                    _ => {
+                        eprintln!("{USO}");
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--salida" => match args.next() {
+                Some(directorio) => salida = PathBuf::from(directorio),
+                None => {
+                    eprintln!("{USO}");
+                    return ExitCode::FAILURE;
+                }
+            },
+            _ if snapshot.is_none() && !arg.starts_with("--") => snapshot = Some(arg),
+            _ => {
+                eprintln!("{USO}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let Some(snapshot) = snapshot else {
+        eprintln!("{USO}");
+        return ExitCode::FAILURE;
+    };
+
+    let resultado = leer_snapshot(&snapshot)
+        .and_then(|ledger| datos_contrato(&ledger, contrato.as_deref()))
+        .and_then(|datos| {
+            let tablas = extraer_tablas(&datos);
+            let resumen = exportar(&tablas, formato, &salida)?;
+            Ok((tablas, resumen, digest_documentos(&datos)?))
+        });
+
+    match resultado {
+        Ok((tablas, resumen, digest)) => {
+            for tabla in &tablas {
+                println!("{}: {} filas", tabla.nombre, tabla.filas.len());
+            }
+            // Valores para `exportar_datos` y para comparar con `consultar_respaldo`
+            println!("hash: {}", hex::encode(resumen.hash));
+            println!("filas: {}", resumen.filas);
+            println!("digest documentos: {}", hex::encode(digest));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Contrasta una tabla de documentos exportada con el digest de un respaldo,
+/// dado en hexadecimal (el de `consultar_respaldo`) o leído de un snapshot.
+fn verificar(mut args: impl Iterator<Item = String>) -> ExitCode {
+    let mut exportacion = None;
+    let mut digest = None;
+    let mut snapshot = None;
+    let mut respaldo = None;
+    let mut contrato = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--digest" => digest = args.next(),
+            "--snapshot" => snapshot = args.next(),
+            "--respaldo" => respaldo = args.next().and_then(|id| id.parse::<i32>().ok()),
+            "--contrato" => contrato = args.next(),
+            _ if exportacion.is_none() && !arg.starts_with("--") => exportacion = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("{USO}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let Some((exportacion, formato)) = exportacion.and_then(|ruta| Formato::de_ruta(&ruta).map(|formato| (ruta, formato))) else {
+        eprintln!("{USO}");
+        return ExitCode::FAILURE;
+    };
+
+    let esperado = match (digest, snapshot, respaldo) {
+        (Some(digest), None, None) => match hex::decode(&digest).ok().and_then(|d| <[u8; 32]>::try_from(d).ok()) {
+            Some(digest) => Ok(digest),
+            None => {
+                eprintln!("error: digest inválido, se esperan 64 dígitos hexadecimales: {digest}");
+                return ExitCode::FAILURE;
+            }
+        },
+        (None, Some(snapshot), Some(respaldo)) => {
+            leer_snapshot(&snapshot).and_then(|ledger| digest_respaldo(&ledger, contrato.as_deref(), respaldo))
+        }
+        _ => {
+            eprintln!("{USO}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match esperado.and_then(|digest| verificar_exportacion(&exportacion, formato, &digest)) {
+        Ok(true) => {
+            println!("la exportación coincide con el respaldo");
+            ExitCode::SUCCESS
+        }
+        Ok(false) => {
+            eprintln!("la exportación NO coincide con el respaldo");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
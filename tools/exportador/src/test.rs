@@ -0,0 +1,153 @@
+#![cfg(test)]
+
+use super::*;
+use std::path::PathBuf;
+
+fn snapshot_prueba(nombre: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../contracts/certificado/test_snapshots/test")
+        .join(nombre)
+}
+
+#[test]
+fn test_extraer_tablas() {
+    let ledger = leer_snapshot(snapshot_prueba("test_flujo_completo_documento.1.json")).unwrap();
+    let datos = datos_contrato(&ledger, None).unwrap();
+    let tablas = extraer_tablas(&datos);
+
+    let nombres: Vec<&str> = tablas.iter().map(|t| t.nombre).collect();
+    assert_eq!(nombres, ["documentos", "historial", "usuarios", "instituciones"]);
+
+    let documentos = &tablas[0];
+    assert_eq!(documentos.columnas, ["id", "titulo", "estado", "fecha_emision_academica"]);
+    assert_eq!(
+        documentos.filas,
+        vec![vec![
+            Value::from(1),
+            Value::from("Certificado de Estudios"),
+            Value::from("Validado"),
+            Value::from(1640995200u64),
+        ]]
+    );
+    assert!(tablas[1].filas.is_empty());
+    // El administrador y el registrador del documento
+    assert_eq!(tablas[2].filas.len(), 2);
+    assert!(tablas[3].filas.is_empty());
+}
+
+#[test]
+fn test_escribir_jsonl_y_csv() {
+    let tabla = Tabla {
+        nombre: "documentos",
+        columnas: vec!["id".into(), "titulo".into(), "fecha".into()],
+        filas: vec![vec![
+            Value::from(1),
+            Value::from("Diploma, \"con honores\""),
+            Value::Null,
+        ]],
+    };
+
+    let mut jsonl = Vec::new();
+    escribir(&tabla, Formato::Jsonl, &mut jsonl).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&jsonl).unwrap(),
+        "{\"id\":1,\"titulo\":\"Diploma, \\\"con honores\\\"\",\"fecha\":null}\n"
+    );
+
+    let mut csv = Vec::new();
+    escribir(&tabla, Formato::Csv, &mut csv).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&csv).unwrap(),
+        "id,titulo,fecha\n1,\"Diploma, \"\"con honores\"\"\",\n"
+    );
+}
+
+#[test]
+fn test_digest_coincide_con_respaldo() {
+    // En este snapshot el respaldo 2 se tomó sobre el estado final del registro
+    let ledger = leer_snapshot(snapshot_prueba("test_respaldo_digest.1.json")).unwrap();
+    let datos = datos_contrato(&ledger, None).unwrap();
+
+    let respaldos = ledger
+        .entries()
+        .into_iter()
+        .find_map(|(_, (entrada, _))| match &entrada.data {
+            LedgerEntryData::ContractData(d) if d.key == ScVal::Symbol("RESPALDOS".try_into().unwrap()) => {
+                Some(d.val.clone())
+            }
+            _ => None,
+        })
+        .unwrap();
+    let respaldo = entradas(Some(&respaldos))
+        .into_iter()
+        .find(|(id, _)| **id == ScVal::I32(2))
+        .map(|(_, r)| valor_json(r))
+        .unwrap();
+
+    assert_eq!(respaldo["digest"], Value::from(hex::encode(digest_documentos(&datos).unwrap())));
+    assert_eq!(respaldo["digest"], Value::from(hex::encode(digest_respaldo(&ledger, None, 2).unwrap())));
+    assert!(matches!(digest_respaldo(&ledger, None, 99), Err(Error::RespaldoNoEncontrado(99))));
+}
+
+#[test]
+fn test_verificar_exportacion() {
+    let ledger = leer_snapshot(snapshot_prueba("test_respaldo_digest.1.json")).unwrap();
+    let digest = digest_respaldo(&ledger, None, 2).unwrap();
+    let tablas = extraer_tablas(&datos_contrato(&ledger, None).unwrap());
+
+    for formato in [Formato::Jsonl, Formato::Csv] {
+        let directorio = std::env::temp_dir().join(format!("exportador_test_verificar_{}", formato.extension()));
+        exportar(&tablas, formato, &directorio).unwrap();
+        let documentos = directorio.join(format!("documentos.{}", formato.extension()));
+        assert!(verificar_exportacion(&documentos, formato, &digest).unwrap());
+
+        // Cualquier cambio en un documento exportado rompe la coincidencia
+        let original = fs::read_to_string(&documentos).unwrap();
+        fs::write(&documentos, original.replacen("Activo", "Validado", 1)).unwrap();
+        assert_ne!(fs::read_to_string(&documentos).unwrap(), original);
+        assert!(!verificar_exportacion(&documentos, formato, &digest).unwrap());
+    }
+
+    let directorio = std::env::temp_dir().join("exportador_test_verificar_invalida");
+    fs::create_dir_all(&directorio).unwrap();
+    let invalida = directorio.join("documentos.jsonl");
+    fs::write(&invalida, "{\"id\":1,\"titulo\":\"Diploma\"}\n").unwrap();
+    assert!(matches!(verificar_exportacion(&invalida, Formato::Jsonl, &digest), Err(Error::Exportacion(_))));
+}
+
+#[test]
+fn test_registros_csv() {
+    assert_eq!(
+        registros_csv("id,titulo\n1,\"Diploma, \"\"con honores\"\"\"\n2,\"a\nb\"\n").unwrap(),
+        vec![
+            vec!["id".to_string(), "titulo".to_string()],
+            vec!["1".to_string(), "Diploma, \"con honores\"".to_string()],
+            vec!["2".to_string(), "a\nb".to_string()],
+        ]
+    );
+    assert!(registros_csv("1,\"abierto\n").is_err());
+}
+
+#[test]
+fn test_exportar_resumen() {
+    let ledger = leer_snapshot(snapshot_prueba("test_multiples_documentos.1.json")).unwrap();
+    let tablas = extraer_tablas(&datos_contrato(&ledger, None).unwrap());
+    let directorio = std::env::temp_dir().join("exportador_test_exportar_resumen");
+
+    let resumen = exportar(&tablas, Formato::Csv, &directorio).unwrap();
+    // 5 documentos más los perfiles del administrador y del registrador
+    assert_eq!(resumen.filas, 7);
+
+    let mut hasher = Sha256::new();
+    for tabla in &tablas {
+        hasher.update(fs::read(directorio.join(format!("{}.csv", tabla.nombre))).unwrap());
+    }
+    assert_eq!(resumen.hash, <[u8; 32]>::from(hasher.finalize()));
+}
+
+#[test]
+fn test_contrato_no_encontrado() {
+    let ledger = leer_snapshot(snapshot_prueba("test_multiples_documentos.1.json")).unwrap();
+    let resultado = datos_contrato(&ledger, Some("CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K"));
+    assert!(matches!(resultado, Err(Error::ContratoNoEncontrado)));
+}
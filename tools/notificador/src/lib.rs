@@ -0,0 +1,614 @@
+//! Relé de notificaciones de `DocumentosContract`.
+//!
+//! El contrato no envía nada por sí mismo: por cada suscripción afectada emite
+//! un evento `("notif", suscriptor, id_documento)` con un `Notificacion` como
+//! datos. Este relé lee esos eventos de una fuente local (un archivo JSON
+//! Lines con eventos en formato XDR-JSON, o los eventos de un snapshot de
+//! prueba) y entrega cada aviso por el destino que indica su `canal`:
+//!
+//! * `webhook:http://host:puerto/ruta` - `POST` con el aviso en JSON.
+//! * `smtp:direccion@dominio` - correo a través del servidor SMTP configurado.
+//! * `archivo:avisos.jsonl` - una línea JSON por aviso.
+//!
+//! Cualquiera puede suscribirse con el canal que quiera, así que el canal no
+//! es de fiar: los webhooks sólo van a los hosts que permite el operador, los
+//! correos sólo a direcciones simples y los archivos sólo dentro del
+//! directorio configurado. Tampoco se retransmiten eventos `notif` de otros
+//! contratos: el relé sólo atiende al registro que se le indica.
+//!
+//! Las conexiones tienen un tiempo de espera, y un aviso que no se pudo
+//! entregar por un fallo pasajero (el destino no responde, devuelve un error)
+//! queda pendiente y se reintenta en las siguientes pasadas.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use stellar_xdr::curr::{ContractEvent, ContractEventBody, ScAddress, ScVal};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Evento(String),
+    Canal(String),
+    Entrega(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "error de E/S: {e}"),
+            Error::Evento(e) => write!(f, "evento inválido: {e}"),
+            Error::Canal(c) => write!(f, "canal no soportado: {c}"),
+            Error::Entrega(e) => write!(f, "no se pudo entregar el aviso: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+    /// `true` si reintentar la entrega puede funcionar (el destino no
+    /// respondió o devolvió un error); un canal o un evento inválidos no
+    /// mejoran con el tiempo.
+    pub fn es_transitorio(&self) -> bool {
+        matches!(self, Error::Io(_) | Error::Entrega(_))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Un aviso listo para entregar, extraído de un evento `notif`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Aviso {
+    pub contrato: String,
+    pub suscriptor: String,
+    pub id_documento: i32,
+    pub id_notificacion: i32,
+    pub evento: String,
+    pub estado: String,
+    pub canal: String,
+}
+
+impl Aviso {
+    pub fn a_json(&self) -> Value {
+        json!({
+            "contrato": self.contrato,
+            "suscriptor": self.suscriptor,
+            "id_documento": self.id_documento,
+            "id_notificacion": self.id_notificacion,
+            "evento": self.evento,
+            "estado": self.estado,
+        })
+    }
+
+    /// El aviso completo, canal incluido, para guardarlo como pendiente.
+    fn a_json_con_canal(&self) -> Value {
+        let mut valor = self.a_json();
+        valor["canal"] = json!(self.canal);
+        valor
+    }
+
+    fn desde_json(valor: &Value) -> Option<Aviso> {
+        let texto = |campo: &str| valor[campo].as_str().map(str::to_string);
+        let entero = |campo: &str| valor[campo].as_i64().and_then(|n| i32::try_from(n).ok());
+        Some(Aviso {
+            contrato: texto("contrato")?,
+            suscriptor: texto("suscriptor")?,
+            id_documento: entero("id_documento")?,
+            id_notificacion: entero("id_notificacion")?,
+            evento: texto("evento")?,
+            estado: texto("estado")?,
+            canal: texto("canal")?,
+        })
+    }
+
+    /// Esquema y destino del canal (`smtp:a@b.c` -> `("smtp", "a@b.c")`).
+    pub fn esquema_y_destino(&self) -> Result<(&str, &str), Error> {
+        self.canal
+            .split_once(':')
+            .ok_or_else(|| Error::Canal(self.canal.clone()))
+    }
+}
+
+/// Convierte un evento del contrato en un aviso. Devuelve `None` para los
+/// eventos que no son avisos de suscripción.
+pub fn aviso_desde_evento(evento: &ContractEvent) -> Result<Option<Aviso>, Error> {
+    let ContractEventBody::V0(cuerpo) = &evento.body;
+    let [ScVal::Symbol(nombre), ScVal::Address(suscriptor), ScVal::I32(id_documento)] =
+        cuerpo.topics.as_slice()
+    else {
+        return Ok(None);
+    };
+    if nombre.to_utf8_string_lossy() != "notif" {
+        return Ok(None);
+    }
+
+    let ScVal::Map(Some(campos)) = &cuerpo.data else {
+        return Err(Error::Evento("los datos no son un Notificacion".into()));
+    };
+    let campo = |nombre: &str| {
+        campos
+            .iter()
+            .find(|c| matches!(&c.key, ScVal::Symbol(s) if s.to_utf8_string_lossy() == nombre))
+            .map(|c| &c.val)
+            .ok_or_else(|| Error::Evento(format!("falta el campo {nombre}")))
+    };
+    let texto = |valor: &ScVal| match valor {
+        ScVal::String(s) => Ok(s.to_utf8_string_lossy()),
+        // Variante de un enum `contracttype` sin datos: `[Symbol]`
+        ScVal::Vec(Some(v)) => match v.as_slice() {
+            [ScVal::Symbol(s)] => Ok(s.to_utf8_string_lossy()),
+            _ => Err(Error::Evento(format!("valor inesperado {valor:?}"))),
+        },
+        _ => Err(Error::Evento(format!("valor inesperado {valor:?}"))),
+    };
+    let ScVal::I32(id_notificacion) = campo("id_notificacion")? else {
+        return Err(Error::Evento("id_notificacion no es i32".into()));
+    };
+
+    Ok(Some(Aviso {
+        contrato: evento
+            .contract_id
+            .clone()
+            .map(|id| ScAddress::Contract(id).to_string())
+            .unwrap_or_default(),
+        suscriptor: suscriptor.to_string(),
+        id_documento: *id_documento,
+        id_notificacion: *id_notificacion,
+        evento: texto(campo("evento")?)?,
+        estado: texto(campo("estado")?)?,
+        canal: texto(campo("canal")?)?,
+    }))
+}
+
+/// Acepta tanto un `ContractEvent` como el envoltorio `{"event": ..., "failed_call": ...}`
+/// de los snapshots del SDK. Los eventos de llamadas fallidas se descartan.
+fn evento_desde_json(valor: Value) -> Result<Option<ContractEvent>, Error> {
+    let (evento, fallido) = match valor.get("event") {
+        Some(evento) => (
+            evento.clone(),
+            valor.get("failed_call").and_then(Value::as_bool).unwrap_or(false),
+        ),
+        None => (valor, false),
+    };
+    if fallido {
+        return Ok(None);
+    }
+    serde_json::from_value(evento)
+        .map(Some)
+        .map_err(|e| Error::Evento(e.to_string()))
+}
+
+/// Origen de eventos del contrato.
+pub trait FuenteEventos {
+    /// Devuelve los eventos nuevos desde la última llamada. Un evento
+    /// ilegible se devuelve como error en su lugar, sin detener la lectura.
+    fn siguientes(&mut self) -> Result<Vec<Result<ContractEvent, Error>>, Error>;
+}
+
+/// Sigue un archivo JSON Lines con un evento por línea, como `tail -f`.
+/// Sólo consume líneas completas; una línea a medio escribir se lee en la
+/// siguiente llamada.
+pub struct ArchivoEventos {
+    ruta: PathBuf,
+    posicion: u64,
+}
+
+impl ArchivoEventos {
+    pub fn new(ruta: impl Into<PathBuf>) -> Self {
+        Self::desde(ruta, 0)
+    }
+
+    /// Empieza a leer en `posicion` (bytes), p. ej. un cursor guardado.
+    pub fn desde(ruta: impl Into<PathBuf>, posicion: u64) -> Self {
+        ArchivoEventos {
+            ruta: ruta.into(),
+            posicion,
+        }
+    }
+
+    pub fn posicion(&self) -> u64 {
+        self.posicion
+    }
+}
+
+impl FuenteEventos for ArchivoEventos {
+    fn siguientes(&mut self) -> Result<Vec<Result<ContractEvent, Error>>, Error> {
+        let mut archivo = match fs::File::open(&self.ruta) {
+            Ok(archivo) => archivo,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        archivo.seek(SeekFrom::Start(self.posicion))?;
+
+        let mut eventos = Vec::new();
+        let mut lector = BufReader::new(archivo);
+        let mut linea = String::new();
+        loop {
+            linea.clear();
+            let leidos = match lector.read_line(&mut linea) {
+                Ok(leidos) => leidos,
+                // Lo ya leído se entrega; el resto se relee en la siguiente llamada
+                Err(_) if !eventos.is_empty() => break,
+                Err(e) => return Err(e.into()),
+            };
+            if leidos == 0 || !linea.ends_with('\n') {
+                break;
+            }
+            self.posicion += leidos as u64;
+            if linea.trim().is_empty() {
+                continue;
+            }
+            let evento = serde_json::from_str(&linea)
+                .map_err(|e| Error::Evento(format!("línea ilegible: {e}")))
+                .and_then(evento_desde_json);
+            match evento {
+                Ok(Some(evento)) => eventos.push(Ok(evento)),
+                Ok(None) => {}
+                Err(e) => eventos.push(Err(e)),
+            }
+        }
+        Ok(eventos)
+    }
+}
+
+/// Eventos registrados en un snapshot de prueba de `DocumentosContract`.
+pub fn eventos_de_snapshot(ruta: impl AsRef<Path>) -> Result<Vec<ContractEvent>, Error> {
+    let snapshot: Value = serde_json::from_reader(fs::File::open(ruta)?)
+        .map_err(|e| Error::Evento(e.to_string()))?;
+    let mut eventos = Vec::new();
+    for valor in snapshot["events"].as_array().cloned().unwrap_or_default() {
+        eventos.extend(evento_desde_json(valor)?);
+    }
+    Ok(eventos)
+}
+
+/// Tiempo de espera por defecto para conectar con un destino y para cada
+/// lectura o escritura.
+pub const TIEMPO_ESPERA: Duration = Duration::from_secs(10);
+
+/// Conecta con `direccion` (`host:puerto`) sin esperar más de `espera`, y
+/// aplica el mismo límite a las lecturas y escrituras.
+fn conectar(direccion: &str, espera: Duration) -> Result<TcpStream, Error> {
+    let mut ultimo = None;
+    for destino in direccion.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&destino, espera) {
+            Ok(conexion) => {
+                conexion.set_read_timeout(Some(espera))?;
+                conexion.set_write_timeout(Some(espera))?;
+                return Ok(conexion);
+            }
+            Err(e) => ultimo = Some(e),
+        }
+    }
+    Err(match ultimo {
+        Some(e) => e.into(),
+        None => Error::Entrega(format!("{direccion}: no se pudo resolver")),
+    })
+}
+
+/// Medio de entrega de avisos.
+pub trait Destino {
+    /// Entrega `aviso` a `destino` (la parte del canal tras el esquema).
+    fn entregar(&mut self, destino: &str, aviso: &Aviso) -> Result<(), Error>;
+}
+
+/// `POST` HTTP/1.1 del aviso en JSON. Sólo `http://`; para HTTPS se espera un
+/// proxy local delante del relé. `permitidos` son los `host[:puerto]` a los
+/// que el operador acepta enviar avisos; cualquier otro se rechaza. `espera`
+/// limita la conexión y cada lectura o escritura (ver `TIEMPO_ESPERA`).
+pub struct Webhook {
+    pub permitidos: Vec<String>,
+    pub espera: Duration,
+}
+
+impl Destino for Webhook {
+    fn entregar(&mut self, destino: &str, aviso: &Aviso) -> Result<(), Error> {
+        let resto = destino
+            .strip_prefix("http://")
+            .ok_or_else(|| Error::Canal(format!("webhook:{destino}")))?;
+        let (servidor, ruta) = match resto.find('/') {
+            Some(i) => (&resto[..i], &resto[i..]),
+            None => (resto, "/"),
+        };
+        if !self.permitidos.iter().any(|permitido| permitido == servidor)
+            || ruta.chars().any(|c| c.is_whitespace() || c.is_control())
+        {
+            return Err(Error::Canal(format!("webhook:{destino}")));
+        }
+        let direccion = if servidor.contains(':') {
+            servidor.to_string()
+        } else {
+            format!("{servidor}:80")
+        };
+
+        let cuerpo = aviso.a_json().to_string();
+        let mut conexion = conectar(&direccion, self.espera)?;
+        write!(
+            conexion,
+            "POST {ruta} HTTP/1.1\r\nHost: {servidor}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{cuerpo}",
+            cuerpo.len()
+        )?;
+
+        let mut respuesta = String::new();
+        BufReader::new(conexion).read_line(&mut respuesta)?;
+        match respuesta.split_whitespace().nth(1) {
+            Some(codigo) if codigo.starts_with('2') => Ok(()),
+            _ => Err(Error::Entrega(format!("{destino}: {}", respuesta.trim()))),
+        }
+    }
+}
+
+/// Correo a través de un servidor SMTP sin autenticación (p. ej. un relay
+/// local de la institución). `espera` funciona como en `Webhook`.
+pub struct Smtp {
+    pub servidor: String,
+    pub remitente: String,
+    pub espera: Duration,
+}
+
+impl Smtp {
+    /// Sólo direcciones simples `local@dominio`, sin espacios, saltos de
+    /// línea ni nombres visibles que permitan inyectar órdenes o cabeceras.
+    fn direccion_valida(destino: &str) -> bool {
+        let Some((local, dominio)) = destino.split_once('@') else {
+            return false;
+        };
+        let local_valido = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c);
+        let dominio_valido = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '.';
+        !local.is_empty()
+            && !dominio.is_empty()
+            && local.chars().all(local_valido)
+            && dominio.chars().all(dominio_valido)
+            && !dominio.starts_with('.')
+            && !dominio.ends_with('.')
+    }
+
+    fn esperar(lector: &mut impl BufRead, codigo: &str) -> Result<(), Error> {
+        loop {
+            let mut linea = String::new();
+            if lector.read_line(&mut linea)? == 0 {
+                return Err(Error::Entrega("el servidor SMTP cerró la conexión".into()));
+            }
+            if !linea.starts_with(codigo) {
+                return Err(Error::Entrega(format!("SMTP: {}", linea.trim())));
+            }
+            // Las respuestas de varias líneas usan `250-` salvo la última
+            if linea.as_bytes().get(3) != Some(&b'-') {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Destino for Smtp {
+    fn entregar(&mut self, destino: &str, aviso: &Aviso) -> Result<(), Error> {
+        if !Self::direccion_valida(destino) {
+            return Err(Error::Canal(format!("smtp:{destino}")));
+        }
+        let conexion = conectar(&self.servidor, self.espera)?;
+        let mut lector = BufReader::new(conexion.try_clone()?);
+        let mut escritor = conexion;
+
+        Self::esperar(&mut lector, "220")?;
+        let mut orden = |texto: String, codigo: &str| -> Result<(), Error> {
+            escritor.write_all(texto.as_bytes())?;
+            Self::esperar(&mut lector, codigo)
+        };
+        orden("EHLO certiweb\r\n".into(), "250")?;
+        orden(format!("MAIL FROM:<{}>\r\n", self.remitente), "250")?;
+        orden(format!("RCPT TO:<{destino}>\r\n"), "250")?;
+        orden("DATA\r\n".into(), "354")?;
+        orden(
+            format!(
+                "From: <{}>\r\nTo: <{destino}>\r\nSubject: Documento {}: {}\r\nContent-Type: application/json\r\n\r\n{}\r\n.\r\n",
+                self.remitente,
+                aviso.id_documento,
+                aviso.evento,
+                aviso.a_json()
+            ),
+            "250",
+        )?;
+        orden("QUIT\r\n".into(), "221")
+    }
+}
+
+/// Añade el aviso como una línea JSON al archivo indicado en el canal, que
+/// debe ser un nombre de archivo simple dentro de `directorio`.
+pub struct Archivo {
+    pub directorio: PathBuf,
+}
+
+impl Destino for Archivo {
+    fn entregar(&mut self, destino: &str, aviso: &Aviso) -> Result<(), Error> {
+        let mut componentes = Path::new(destino).components();
+        let nombre = match (componentes.next(), componentes.next()) {
+            (Some(Component::Normal(nombre)), None) => nombre,
+            _ => return Err(Error::Canal(format!("archivo:{destino}"))),
+        };
+        let mut archivo = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.directorio.join(nombre))?;
+        writeln!(archivo, "{}", aviso.a_json())?;
+        Ok(())
+    }
+}
+
+/// Pasadas en las que se reintenta un aviso pendiente antes de descartarlo.
+pub const MAX_INTENTOS: u32 = 20;
+
+/// Aviso que no se pudo entregar por un error transitorio y se reintentará.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pendiente {
+    pub aviso: Aviso,
+    pub intentos: u32,
+}
+
+/// Encamina cada aviso del registro `contrato` al destino registrado para el
+/// esquema de su canal, y guarda los que fallan para reintentarlos.
+pub struct Rele {
+    contrato: String,
+    destinos: HashMap<String, Box<dyn Destino>>,
+    pendientes: Vec<Pendiente>,
+}
+
+impl Rele {
+    /// `contrato` es el ID (`C...`) del registro cuyos avisos se entregan.
+    pub fn new(contrato: impl Into<String>) -> Self {
+        Rele {
+            contrato: contrato.into(),
+            destinos: HashMap::new(),
+            pendientes: Vec::new(),
+        }
+    }
+
+    pub fn con_destino(mut self, esquema: &str, destino: impl Destino + 'static) -> Self {
+        self.destinos.insert(esquema.to_string(), Box::new(destino));
+        self
+    }
+
+    /// Avisos pendientes de una ejecución anterior (ver `leer_pendientes`).
+    pub fn con_pendientes(mut self, pendientes: Vec<Pendiente>) -> Self {
+        self.pendientes = pendientes;
+        self
+    }
+
+    pub fn pendientes(&self) -> &[Pendiente] {
+        &self.pendientes
+    }
+
+    /// Entrega el aviso de un evento. Devuelve el aviso entregado, o `None`
+    /// si el evento no era un aviso de suscripción del registro.
+    pub fn procesar(&mut self, evento: &ContractEvent) -> Result<Option<Aviso>, Error> {
+        let Some(aviso) = self.aviso(evento)? else {
+            return Ok(None);
+        };
+        self.entregar(&aviso)?;
+        Ok(Some(aviso))
+    }
+
+    fn aviso(&self, evento: &ContractEvent) -> Result<Option<Aviso>, Error> {
+        Ok(aviso_desde_evento(evento)?.filter(|aviso| aviso.contrato == self.contrato))
+    }
+
+    fn entregar(&mut self, aviso: &Aviso) -> Result<(), Error> {
+        let (esquema, destino) = aviso.esquema_y_destino()?;
+        let medio = self
+            .destinos
+            .get_mut(esquema)
+            .ok_or_else(|| Error::Canal(aviso.canal.clone()))?;
+        medio.entregar(destino, aviso)
+    }
+
+    /// Reintenta los avisos pendientes y procesa todos los eventos nuevos de
+    /// la fuente. Un aviso que no se puede entregar no detiene a los demás:
+    /// si el fallo es transitorio queda pendiente (hasta `MAX_INTENTOS`
+    /// pasadas) y, en cualquier caso, su error se devuelve aparte, igual que
+    /// los eventos ilegibles.
+    pub fn procesar_fuente(
+        &mut self,
+        fuente: &mut impl FuenteEventos,
+    ) -> Result<(Vec<Aviso>, Vec<Error>), Error> {
+        let mut entregados = Vec::new();
+        let mut errores = Vec::new();
+
+        for mut pendiente in std::mem::take(&mut self.pendientes) {
+            match self.entregar(&pendiente.aviso) {
+                Ok(()) => entregados.push(pendiente.aviso),
+                Err(e) => {
+                    pendiente.intentos += 1;
+                    if e.es_transitorio() && pendiente.intentos < MAX_INTENTOS {
+                        self.pendientes.push(pendiente);
+                        errores.push(e);
+                    } else {
+                        errores.push(Error::Entrega(format!(
+                            "aviso {} descartado tras {} intentos: {e}",
+                            pendiente.aviso.id_notificacion, pendiente.intentos
+                        )));
+                    }
+                }
+            }
+        }
+
+        for evento in fuente.siguientes()? {
+            let aviso = match evento.and_then(|evento| self.aviso(&evento)) {
+                Ok(Some(aviso)) => aviso,
+                Ok(None) => continue,
+                Err(e) => {
+                    errores.push(e);
+                    continue;
+                }
+            };
+            match self.entregar(&aviso) {
+                Ok(()) => entregados.push(aviso),
+                Err(e) => {
+                    if e.es_transitorio() {
+                        self.pendientes.push(Pendiente { aviso, intentos: 1 });
+                    }
+                    errores.push(e);
+                }
+            }
+        }
+        Ok((entregados, errores))
+    }
+}
+
+/// Lee los avisos pendientes guardados con `guardar_pendientes`. Un archivo
+/// que no existe equivale a no tener pendientes.
+pub fn leer_pendientes(ruta: &Path) -> Result<Vec<Pendiente>, Error> {
+    let texto = match fs::read_to_string(ruta) {
+        Ok(texto) => texto,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    texto
+        .lines()
+        .filter(|linea| !linea.trim().is_empty())
+        .map(|linea| {
+            let valor: Value = serde_json::from_str(linea).map_err(|e| Error::Evento(e.to_string()))?;
+            let aviso = Aviso::desde_json(&valor["aviso"])
+                .ok_or_else(|| Error::Evento(format!("pendiente inválido: {linea}")))?;
+            let intentos = valor["intentos"].as_u64().unwrap_or(0) as u32;
+            Ok(Pendiente { aviso, intentos })
+        })
+        .collect()
+}
+
+/// Guarda los avisos pendientes, uno por línea JSON. Se escribe en un archivo
+/// temporal y se renombra, para no dejar la lista a medias.
+pub fn guardar_pendientes(ruta: &Path, pendientes: &[Pendiente]) -> Result<(), Error> {
+    let mut texto = String::new();
+    for pendiente in pendientes {
+        let linea = json!({ "aviso": pendiente.aviso.a_json_con_canal(), "intentos": pendiente.intentos });
+        texto.push_str(&linea.to_string());
+        texto.push('\n');
+    }
+    let temporal = ruta.with_extension("tmp");
+    fs::write(&temporal, texto)?;
+    fs::rename(&temporal, ruta)?;
+    Ok(())
+}
+
+/// Lee un cursor guardado (posición en bytes del archivo de eventos).
+pub fn leer_cursor(ruta: &Path) -> u64 {
+    let mut texto = String::new();
+    fs::File::open(ruta)
+        .and_then(|mut f| f.read_to_string(&mut texto))
+        .ok()
+        .and_then(|_| texto.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use notificador::{
+    guardar_pendientes, leer_cursor, leer_pendientes, Archivo, ArchivoEventos, Rele, Smtp, Webhook, TIEMPO_ESPERA,
+};
+
+/// Con `--cursor`, los avisos pendientes se guardan junto a él con la
+/// extensión `.pendientes`.
+const USO: &str = "uso: notificador <eventos.jsonl> --contrato <C...> [--smtp <host:puerto>] [--remitente <direccion>] \
+[--webhook <host:puerto>]... [--archivos <directorio>] [--intervalo <ms>] [--cursor <archivo>] [--una-vez]";
+
+fn main() -> ExitCode {
+    let mut eventos = None;
+    let mut contrato = None;
+    let mut smtp = None;
+    let mut remitente = String::from("certiweb@localhost");
+    let mut intervalo = Duration::from_millis(2000);
+    let mut cursor: Option<PathBuf> = None;
+    let mut webhooks = Vec::new();
+    let mut archivos: Option<PathBuf> = None;
+    let mut una_vez = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let valor = match arg.as_str() {
+            "--una-vez" => {
+                una_vez = true;
+                continue;
+            }
+            "--contrato" | "--smtp" | "--remitente" | "--intervalo" | "--cursor" | "--webhook" | "--archivos" => {
+                args.next()
+            }
+            _ if eventos.is_none() && !arg.starts_with("--") => {
+                eventos = Some(PathBuf::from(arg));
+                continue;
+            }
+            _ => None,
+        };
+        let Some(valor) = valor else {
+            eprintln!("{USO}");
+            return ExitCode::FAILURE;
+        };
+        match arg.as_str() {
+            "--contrato" => contrato = Some(valor),
+            "--smtp" => smtp = Some(valor),
+            "--remitente" => remitente = valor,
+            "--cursor" => cursor = Some(PathBuf::from(valor)),
+            "--webhook" => webhooks.push(valor),
+            "--archivos" => archivos = Some(PathBuf::from(valor)),
+            _ => match valor.parse() {
+                Ok(ms) => intervalo = Duration::from_millis(ms),
+                Err(_) => {
+                    eprintln!("{USO}");
+                    return ExitCode::FAILURE;
+                }
+            },
+        }
+    }
+    let (Some(eventos), Some(contrato)) = (eventos, contrato) else {
+        eprintln!("{USO}");
+        return ExitCode::FAILURE;
+    };
+    let pendientes = cursor.as_ref().map(|cursor| cursor.with_extension("pendientes"));
+
+    // Cada destino sólo se habilita si el operador lo configura
+    let mut rele = Rele::new(contrato);
+    if let Some(pendientes) = &pendientes {
+        match leer_pendientes(pendientes) {
+            Ok(guardados) => rele = rele.con_pendientes(guardados),
+            Err(e) => {
+                eprintln!("error: no se pudieron leer los avisos pendientes: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if !webhooks.is_empty() {
+        rele = rele.con_destino(
+            "webhook",
+            Webhook {
+                permitidos: webhooks,
+                espera: TIEMPO_ESPERA,
+            },
+        );
+    }
+    if let Some(directorio) = archivos {
+        rele = rele.con_destino("archivo", Archivo { directorio });
+    }
+    if let Some(servidor) = smtp {
+        rele = rele.con_destino(
+            "smtp",
+            Smtp {
+                servidor,
+                remitente,
+                espera: TIEMPO_ESPERA,
+            },
+        );
+    }
+
+    let inicio = cursor.as_deref().map(leer_cursor).unwrap_or(0);
+    let mut fuente = ArchivoEventos::desde(eventos, inicio);
+    loop {
+        match rele.procesar_fuente(&mut fuente) {
+            Ok((entregados, errores)) => {
+                for aviso in entregados {
+                    println!(
+                        "entregado: documento {} ({}) -> {}",
+                        aviso.id_documento, aviso.evento, aviso.canal
+                    );
+                }
+                for error in errores {
+                    eprintln!("error: {error}");
+                }
+            }
+            Err(e) => {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        // Los pendientes se guardan antes que el cursor: si no se pueden
+        // guardar, el cursor no avanza y los avisos se releen de la fuente
+        let pendientes_guardados = match &pendientes {
+            Some(pendientes) => match guardar_pendientes(pendientes, rele.pendientes()) {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("error: no se pudieron guardar los avisos pendientes: {e}");
+                    false
+                }
+            },
+            None => true,
+        };
+        if let (Some(cursor), true) = (&cursor, pendientes_guardados) {
+            if let Err(e) = std::fs::write(cursor, fuente.posicion().to_string()) {
+                eprintln!("error: no se pudo guardar el cursor: {e}");
+            }
+        }
+        if una_vez {
+            return ExitCode::SUCCESS;
+        }
+        thread::sleep(intervalo);
+    }
+}
@@ -0,0 +1,416 @@
+#![cfg(test)]
+
+use super::*;
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use stellar_xdr::curr::ScString;
+
+fn snapshot_prueba(nombre: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../contracts/certificado/test_snapshots/test")
+        .join(nombre)
+}
+
+fn directorio_prueba(nombre: &str) -> PathBuf {
+    let directorio = std::env::temp_dir().join(format!("notificador_{nombre}"));
+    let _ = fs::remove_dir_all(&directorio);
+    fs::create_dir_all(&directorio).unwrap();
+    directorio
+}
+
+fn aviso(canal: &str) -> Aviso {
+    Aviso {
+        contrato: "C".into(),
+        suscriptor: "G".into(),
+        id_documento: 1,
+        id_notificacion: 7,
+        evento: "Revocacion".into(),
+        estado: "Revocado".into(),
+        canal: canal.into(),
+    }
+}
+
+/// ID del contrato que emitió los eventos del snapshot.
+fn contrato_prueba() -> String {
+    aviso_desde_evento(&evento_con_canal("archivo:avisos.jsonl")).unwrap().unwrap().contrato
+}
+
+/// Un evento `notif` como el que emite el contrato, cambiando sólo el canal.
+fn evento_con_canal(canal: &str) -> ContractEvent {
+    let mut evento = eventos_de_snapshot(snapshot_prueba("test_notificaciones_por_evento.1.json"))
+        .unwrap()
+        .remove(1);
+    let ContractEventBody::V0(cuerpo) = &mut evento.body;
+    let ScVal::Map(Some(campos)) = &mut cuerpo.data else {
+        unreachable!()
+    };
+    let mut entradas = campos.to_vec();
+    for entrada in entradas.iter_mut() {
+        if entrada.key == ScVal::Symbol("canal".try_into().unwrap()) {
+            entrada.val = ScVal::String(ScString(canal.try_into().unwrap()));
+        }
+    }
+    *campos = entradas.try_into().unwrap();
+    evento
+}
+
+#[test]
+fn test_avisos_de_snapshot() {
+    let eventos = eventos_de_snapshot(snapshot_prueba("test_notificaciones_por_evento.1.json")).unwrap();
+    let avisos: Vec<Aviso> = eventos
+        .iter()
+        .filter_map(|e| aviso_desde_evento(e).unwrap())
+        .collect();
+
+    // La revocación del documento 1 avisa al verificador (cambio de estado) y al titular
+    assert_eq!(avisos.len(), 2);
+    assert_eq!(avisos[0].evento, "CambioEstado");
+    assert_eq!(avisos[0].canal, "webhook:http://127.0.0.1:8080/avisos");
+    assert_eq!(avisos[1].evento, "Revocacion");
+    assert_eq!(avisos[1].estado, "Revocado");
+    assert_eq!(avisos[1].id_documento, 1);
+    assert_eq!(avisos[1].canal, "smtp:titular@example.com");
+    assert!(avisos[1].contrato.starts_with('C'));
+}
+
+#[test]
+fn test_archivo_eventos_lee_solo_lineas_completas() {
+    let directorio = directorio_prueba("archivo_eventos");
+    let ruta = directorio.join("eventos.jsonl");
+    let evento = serde_json::to_string(&evento_con_canal("archivo:avisos.jsonl")).unwrap();
+
+    fs::write(&ruta, format!("{evento}\n{}", &evento[..10])).unwrap();
+    let mut fuente = ArchivoEventos::new(&ruta);
+    assert_eq!(fuente.siguientes().unwrap().len(), 1);
+
+    // La línea a medio escribir se completa y se lee en la siguiente llamada
+    let mut archivo = OpenOptions::new().append(true).open(&ruta).unwrap();
+    writeln!(archivo, "{}", &evento[10..]).unwrap();
+    assert_eq!(fuente.siguientes().unwrap().len(), 1);
+    assert!(fuente.siguientes().unwrap().is_empty());
+}
+
+#[test]
+fn test_destino_archivo() {
+    let directorio = directorio_prueba("destino_archivo");
+    let salida = directorio.join("avisos.jsonl");
+    let mut rele = Rele::new(contrato_prueba()).con_destino("archivo", Archivo { directorio });
+
+    let entregado = rele.procesar(&evento_con_canal("archivo:avisos.jsonl")).unwrap().unwrap();
+    assert_eq!(entregado.evento, "Revocacion");
+
+    let linea: Value = serde_json::from_str(fs::read_to_string(&salida).unwrap().trim()).unwrap();
+    assert_eq!(linea["id_documento"], 1);
+    assert_eq!(linea["estado"], "Revocado");
+}
+
+#[test]
+fn test_destino_archivo_fuera_del_directorio() {
+    let directorio = directorio_prueba("destino_archivo_fuera");
+    let mut destino = Archivo { directorio: directorio.join("avisos") };
+
+    for ruta in ["/tmp/avisos.jsonl", "../avisos.jsonl", "avisos/../../x", ""] {
+        let resultado = destino.entregar(ruta, &aviso("archivo:"));
+        assert!(matches!(resultado, Err(Error::Canal(_))), "{ruta}");
+    }
+    assert!(fs::read_dir(&directorio).unwrap().next().is_none());
+}
+
+#[test]
+fn test_destino_webhook() {
+    let servidor = TcpListener::bind("127.0.0.1:0").unwrap();
+    let puerto = servidor.local_addr().unwrap().port();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (conexion, _) = servidor.accept().unwrap();
+        let mut lector = BufReader::new(conexion.try_clone().unwrap());
+        let mut peticion = String::new();
+        let mut largo = 0;
+        loop {
+            let mut linea = String::new();
+            lector.read_line(&mut linea).unwrap();
+            if let Some(valor) = linea.strip_prefix("Content-Length: ") {
+                largo = valor.trim().parse().unwrap();
+            }
+            peticion.push_str(&linea);
+            if linea == "\r\n" {
+                break;
+            }
+        }
+        let mut cuerpo = vec![0; largo];
+        lector.read_exact(&mut cuerpo).unwrap();
+        (&conexion).write_all(b"HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+        tx.send((peticion, String::from_utf8(cuerpo).unwrap())).unwrap();
+    });
+
+    let mut webhook = Webhook {
+        permitidos: vec![format!("127.0.0.1:{puerto}")],
+        espera: TIEMPO_ESPERA,
+    };
+    webhook
+        .entregar(&format!("http://127.0.0.1:{puerto}/avisos"), &aviso("webhook:"))
+        .unwrap();
+
+    let (peticion, cuerpo) = rx.recv().unwrap();
+    assert!(peticion.starts_with("POST /avisos HTTP/1.1\r\n"));
+    let cuerpo: Value = serde_json::from_str(&cuerpo).unwrap();
+    assert_eq!(cuerpo["evento"], "Revocacion");
+    assert_eq!(cuerpo["id_notificacion"], 7);
+}
+
+#[test]
+fn test_destino_webhook_rechazado() {
+    let servidor = TcpListener::bind("127.0.0.1:0").unwrap();
+    let puerto = servidor.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (conexion, _) = servidor.accept().unwrap();
+        let mut lector = BufReader::new(conexion.try_clone().unwrap());
+        let mut linea = String::new();
+        while linea != "\r\n" {
+            linea.clear();
+            lector.read_line(&mut linea).unwrap();
+        }
+        (&conexion).write_all(b"HTTP/1.1 500 Internal Server Error\r\n\r\n").unwrap();
+    });
+
+    let mut webhook = Webhook {
+        permitidos: vec![format!("127.0.0.1:{puerto}")],
+        espera: TIEMPO_ESPERA,
+    };
+    let resultado = webhook.entregar(&format!("http://127.0.0.1:{puerto}/"), &aviso("webhook:"));
+    assert!(matches!(resultado, Err(Error::Entrega(_))));
+}
+
+#[test]
+fn test_destino_webhook_no_permitido() {
+    let mut webhook = Webhook {
+        permitidos: vec!["avisos.example.com".into()],
+        espera: TIEMPO_ESPERA,
+    };
+    for destino in [
+        "http://127.0.0.1:8080/avisos",
+        "http://avisos.example.com:8080/",
+        "http://avisos.example.com/x HTTP/1.1\r\nHost: otro\r\n\r\n",
+    ] {
+        let resultado = webhook.entregar(destino, &aviso("webhook:"));
+        assert!(matches!(resultado, Err(Error::Canal(_))), "{destino}");
+    }
+}
+
+#[test]
+fn test_destino_smtp() {
+    let servidor = TcpListener::bind("127.0.0.1:0").unwrap();
+    let direccion = servidor.local_addr().unwrap().to_string();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (conexion, _) = servidor.accept().unwrap();
+        let mut lector = BufReader::new(conexion.try_clone().unwrap());
+        let mut escritor = conexion;
+        let mut sesion = Vec::new();
+        escritor.write_all(b"220 prueba ESMTP\r\n").unwrap();
+        loop {
+            let mut linea = String::new();
+            lector.read_line(&mut linea).unwrap();
+            let respuesta: &[u8] = if linea.starts_with("EHLO") {
+                b"250-prueba\r\n250 OK\r\n"
+            } else if linea.starts_with("DATA") {
+                b"354 Adelante\r\n"
+            } else if linea.starts_with("QUIT") {
+                b"221 Adios\r\n"
+            } else if linea == ".\r\n" || linea.starts_with("MAIL") || linea.starts_with("RCPT") {
+                b"250 OK\r\n"
+            } else {
+                sesion.push(linea);
+                continue;
+            };
+            sesion.push(linea.clone());
+            escritor.write_all(respuesta).unwrap();
+            if linea.starts_with("QUIT") {
+                break;
+            }
+        }
+        tx.send(sesion.concat()).unwrap();
+    });
+
+    let mut rele = Rele::new(contrato_prueba()).con_destino(
+        "smtp",
+        Smtp {
+            servidor: direccion,
+            remitente: "avisos@certiweb.test".into(),
+            espera: TIEMPO_ESPERA,
+        },
+    );
+    let entregado = rele.procesar(&evento_con_canal("smtp:titular@example.com")).unwrap();
+    assert!(entregado.is_some());
+
+    let sesion = rx.recv().unwrap();
+    assert!(sesion.contains("MAIL FROM:<avisos@certiweb.test>\r\n"));
+    assert!(sesion.contains("RCPT TO:<titular@example.com>\r\n"));
+    assert!(sesion.contains("Subject: Documento 1: Revocacion\r\n"));
+}
+
+#[test]
+fn test_destino_smtp_direccion_invalida() {
+    // Se rechaza antes de conectar, así que el servidor no hace falta
+    let mut smtp = Smtp {
+        servidor: "127.0.0.1:1".into(),
+        remitente: "avisos@certiweb.test".into(),
+        espera: TIEMPO_ESPERA,
+    };
+    for destino in [
+        "titular@example.com>\r\nRCPT TO:<otro@example.com",
+        "titular@example.com\nBcc: otro@example.com",
+        "Titular <titular@example.com>",
+        "titular",
+        "@example.com",
+    ] {
+        let resultado = smtp.entregar(destino, &aviso("smtp:"));
+        assert!(matches!(resultado, Err(Error::Canal(_))), "{destino}");
+    }
+}
+
+#[test]
+fn test_canal_sin_destino() {
+    let mut rele = Rele::new(contrato_prueba()).con_destino(
+        "archivo",
+        Archivo {
+            directorio: std::env::temp_dir(),
+        },
+    );
+    let resultado = rele.procesar(&evento_con_canal("sms:+5210000000000"));
+    assert!(matches!(resultado, Err(Error::Canal(_))));
+}
+
+#[test]
+fn test_procesar_fuente_continua_tras_error() {
+    let directorio = directorio_prueba("procesar_fuente");
+    let ruta = directorio.join("eventos.jsonl");
+    let salida = directorio.join("avisos.jsonl");
+    let lineas = [
+        serde_json::to_string(&evento_con_canal("sms:+5210000000000")).unwrap(),
+        serde_json::to_string(&evento_con_canal("archivo:avisos.jsonl")).unwrap(),
+    ];
+    fs::write(&ruta, lineas.join("\n") + "\n").unwrap();
+
+    let mut rele = Rele::new(contrato_prueba()).con_destino("archivo", Archivo { directorio });
+    let mut fuente = ArchivoEventos::new(&ruta);
+    let (entregados, errores) = rele.procesar_fuente(&mut fuente).unwrap();
+    assert_eq!(entregados.len(), 1);
+    assert_eq!(errores.len(), 1);
+    assert_eq!(fs::read_to_string(&salida).unwrap().lines().count(), 1);
+}
+
+#[test]
+fn test_procesar_fuente_salta_lineas_ilegibles() {
+    let directorio = directorio_prueba("lineas_ilegibles");
+    let ruta = directorio.join("eventos.jsonl");
+    let evento = serde_json::to_string(&evento_con_canal("archivo:avisos.jsonl")).unwrap();
+    fs::write(&ruta, format!("{{no es json\n{{\"otro\": 1}}\n{evento}\n")).unwrap();
+
+    let mut rele = Rele::new(contrato_prueba()).con_destino("archivo", Archivo { directorio: directorio.clone() });
+    let mut fuente = ArchivoEventos::new(&ruta);
+    let (entregados, errores) = rele.procesar_fuente(&mut fuente).unwrap();
+    assert_eq!(entregados.len(), 1);
+    assert_eq!(errores.len(), 2);
+    assert!(errores.iter().all(|e| matches!(e, Error::Evento(_))));
+    // Las líneas ilegibles no se vuelven a leer
+    assert_eq!(fuente.posicion(), fs::metadata(&ruta).unwrap().len());
+}
+
+#[test]
+fn test_rele_ignora_otros_contratos() {
+    let directorio = directorio_prueba("otros_contratos");
+    let mut rele = Rele::new("CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC")
+        .con_destino("archivo", Archivo { directorio: directorio.clone() });
+
+    assert_eq!(rele.procesar(&evento_con_canal("archivo:avisos.jsonl")).unwrap(), None);
+    assert!(fs::read_dir(&directorio).unwrap().next().is_none());
+}
+
+/// Destino que falla las primeras `fallos` entregas.
+struct Intermitente {
+    fallos: u32,
+    entregados: std::rc::Rc<std::cell::Cell<u32>>,
+}
+
+impl Destino for Intermitente {
+    fn entregar(&mut self, _destino: &str, _aviso: &Aviso) -> Result<(), Error> {
+        if self.fallos > 0 {
+            self.fallos -= 1;
+            return Err(Error::Entrega("no disponible".into()));
+        }
+        self.entregados.set(self.entregados.get() + 1);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pendientes_se_reintentan() {
+    let directorio = directorio_prueba("pendientes");
+    let ruta = directorio.join("eventos.jsonl");
+    let evento = serde_json::to_string(&evento_con_canal("webhook:http://127.0.0.1:9/avisos")).unwrap();
+    fs::write(&ruta, format!("{evento}\n")).unwrap();
+
+    let entregados = std::rc::Rc::new(std::cell::Cell::new(0));
+    let destino = || Intermitente {
+        fallos: 1,
+        entregados: entregados.clone(),
+    };
+    let mut rele = Rele::new(contrato_prueba()).con_destino("webhook", destino());
+    let mut fuente = ArchivoEventos::new(&ruta);
+    let (_, errores) = rele.procesar_fuente(&mut fuente).unwrap();
+    assert_eq!(errores.len(), 1);
+    assert_eq!(rele.pendientes().len(), 1);
+
+    // Los pendientes sobreviven a un reinicio del relé
+    let guardados = directorio.join("cursor.pendientes");
+    guardar_pendientes(&guardados, rele.pendientes()).unwrap();
+    let leidos = leer_pendientes(&guardados).unwrap();
+    assert_eq!(leidos, rele.pendientes());
+
+    let mut rele = Rele::new(contrato_prueba())
+        .con_destino("webhook", Intermitente { fallos: 0, entregados: entregados.clone() })
+        .con_pendientes(leidos);
+    let mut fuente = ArchivoEventos::desde(&ruta, fuente.posicion());
+    let (reintentados, errores) = rele.procesar_fuente(&mut fuente).unwrap();
+    assert_eq!(reintentados.len(), 1);
+    assert!(errores.is_empty());
+    assert!(rele.pendientes().is_empty());
+    assert_eq!(entregados.get(), 1);
+}
+
+#[test]
+fn test_pendiente_descartado_tras_max_intentos() {
+    let entregados = std::rc::Rc::new(std::cell::Cell::new(0));
+    let pendiente = Pendiente {
+        aviso: aviso("webhook:http://127.0.0.1:9/"),
+        intentos: MAX_INTENTOS - 1,
+    };
+    let mut rele = Rele::new(contrato_prueba())
+        .con_destino("webhook", Intermitente { fallos: 1, entregados })
+        .con_pendientes(vec![pendiente]);
+    let (_, errores) = rele.procesar_fuente(&mut ArchivoEventos::new("/no/existe.jsonl")).unwrap();
+    assert_eq!(errores.len(), 1);
+    assert!(rele.pendientes().is_empty());
+}
+
+#[test]
+fn test_destino_webhook_tiempo_espera() {
+    // Acepta la conexión pero nunca responde
+    let servidor = TcpListener::bind("127.0.0.1:0").unwrap();
+    let puerto = servidor.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (_conexion, _) = servidor.accept().unwrap();
+        thread::sleep(std::time::Duration::from_secs(5));
+    });
+
+    let mut webhook = Webhook {
+        permitidos: vec![format!("127.0.0.1:{puerto}")],
+        espera: std::time::Duration::from_millis(200),
+    };
+    let inicio = std::time::Instant::now();
+    let resultado = webhook.entregar(&format!("http://127.0.0.1:{puerto}/"), &aviso("webhook:"));
+    assert!(inicio.elapsed() < std::time::Duration::from_secs(2));
+    assert!(resultado.unwrap_err().es_transitorio());
+}